@@ -0,0 +1,397 @@
+// POSIX ACLs, read and written through their extended attributes.
+//
+// The kernel stores them as a little-endian u32 version (2) followed by one
+// 8-byte record per entry: u16 tag, u16 permission bits (r=4, w=2, x=1) and a
+// u32 uid/gid that is only meaningful for named user and group entries.
+#![cfg_attr(not(unix), allow(dead_code))]
+
+use crate::metadata::NameCache;
+use crate::pathenc;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+
+const ACCESS_XATTR: &str = "system.posix_acl_access";
+const DEFAULT_XATTR: &str = "system.posix_acl_default";
+const ACL_VERSION: u32 = 2;
+const UNDEFINED_ID: u32 = u32::MAX;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum AclTag {
+    UserObj,
+    User,
+    GroupObj,
+    Group,
+    Mask,
+    Other,
+}
+
+impl AclTag {
+    fn from_raw(tag: u16) -> Option<Self> {
+        match tag {
+            0x01 => Some(AclTag::UserObj),
+            0x02 => Some(AclTag::User),
+            0x04 => Some(AclTag::GroupObj),
+            0x08 => Some(AclTag::Group),
+            0x10 => Some(AclTag::Mask),
+            0x20 => Some(AclTag::Other),
+            _ => None,
+        }
+    }
+
+    fn raw(self) -> u16 {
+        match self {
+            AclTag::UserObj => 0x01,
+            AclTag::User => 0x02,
+            AclTag::GroupObj => 0x04,
+            AclTag::Group => 0x08,
+            AclTag::Mask => 0x10,
+            AclTag::Other => 0x20,
+        }
+    }
+
+    fn is_named(self) -> bool {
+        matches!(self, AclTag::User | AclTag::Group)
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, Default, PartialEq, Eq)]
+pub struct AclPerms {
+    pub read: bool,
+    pub write: bool,
+    pub execute: bool,
+}
+
+impl AclPerms {
+    fn from_bits(bits: u16) -> Self {
+        AclPerms {
+            read: bits & 4 != 0,
+            write: bits & 2 != 0,
+            execute: bits & 1 != 0,
+        }
+    }
+
+    fn bits(self) -> u16 {
+        (self.read as u16) << 2 | (self.write as u16) << 1 | self.execute as u16
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct AclEntry {
+    pub tag: AclTag,
+    /// uid or gid for named `user` and `group` entries
+    #[serde(default)]
+    pub id: Option<u32>,
+    /// User or group name; accepted instead of `id` when setting
+    #[serde(default)]
+    pub name: Option<String>,
+    pub perms: AclPerms,
+    /// What the entry actually grants once the mask is applied (ignored when setting)
+    #[serde(default)]
+    pub effective: AclPerms,
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct Acl {
+    pub access: Vec<AclEntry>,
+    /// Inherited by new items in a directory; empty when there is none
+    pub default: Vec<AclEntry>,
+    /// The access ACL has entries beyond what the mode bits express
+    pub extended: bool,
+}
+
+fn decode(bytes: &[u8]) -> Result<Vec<AclEntry>, String> {
+    let header = bytes.get(..4).ok_or("ACL too short")?;
+    if u32::from_le_bytes(header.try_into().unwrap_or_default()) != ACL_VERSION {
+        return Err("Unsupported ACL version".to_string());
+    }
+    let records = bytes[4..].chunks_exact(8);
+    if !records.remainder().is_empty() {
+        return Err("Malformed ACL".to_string());
+    }
+    records
+        .map(|r| {
+            let raw_tag = u16::from_le_bytes([r[0], r[1]]);
+            let tag = AclTag::from_raw(raw_tag).ok_or_else(|| format!("Unknown ACL tag: {:#x}", raw_tag))?;
+            let id = u32::from_le_bytes([r[4], r[5], r[6], r[7]]);
+            let perms = AclPerms::from_bits(u16::from_le_bytes([r[2], r[3]]));
+            Ok(AclEntry {
+                tag,
+                id: (tag.is_named() && id != UNDEFINED_ID).then_some(id),
+                name: None,
+                perms,
+                effective: perms,
+            })
+        })
+        .collect()
+}
+
+fn encode(entries: &[AclEntry]) -> Vec<u8> {
+    let mut out = ACL_VERSION.to_le_bytes().to_vec();
+    for e in entries {
+        out.extend_from_slice(&e.tag.raw().to_le_bytes());
+        out.extend_from_slice(&e.perms.bits().to_le_bytes());
+        out.extend_from_slice(&e.id.unwrap_or(UNDEFINED_ID).to_le_bytes());
+    }
+    out
+}
+
+// The three entries every file has, taken from its mode bits
+fn from_mode(mode: u32) -> Vec<AclEntry> {
+    [(AclTag::UserObj, 6), (AclTag::GroupObj, 3), (AclTag::Other, 0)]
+        .into_iter()
+        .map(|(tag, shift)| {
+            let perms = AclPerms::from_bits(((mode >> shift) & 7) as u16);
+            AclEntry {
+                tag,
+                id: None,
+                name: None,
+                perms,
+                effective: perms,
+            }
+        })
+        .collect()
+}
+
+// Fill in names and apply the mask to the group class
+fn annotate(entries: &mut [AclEntry], names: &mut NameCache) {
+    let mask = entries.iter().find(|e| e.tag == AclTag::Mask).map(|e| e.perms.bits());
+    for e in entries.iter_mut() {
+        e.name = match (e.tag, e.id) {
+            (AclTag::User, Some(id)) => names.user(id),
+            (AclTag::Group, Some(id)) => names.group(id),
+            _ => None,
+        };
+        let masked = matches!(e.tag, AclTag::User | AclTag::GroupObj | AclTag::Group);
+        e.effective = match mask {
+            Some(mask) if masked => AclPerms::from_bits(e.perms.bits() & mask),
+            _ => e.perms,
+        };
+    }
+}
+
+// Resolve names, check the entry set is well-formed, add a mask if named
+// entries need one, and sort the way the kernel expects
+#[cfg(unix)]
+fn normalize(mut entries: Vec<AclEntry>) -> Result<Vec<AclEntry>, String> {
+    for e in entries.iter_mut() {
+        if !e.tag.is_named() {
+            e.id = None;
+            continue;
+        }
+        if e.id.is_none() {
+            let name = e.name.as_deref().ok_or("Named ACL entry without id or name")?;
+            e.id = Some(match e.tag {
+                AclTag::User => crate::properties::resolve_user(name)?,
+                _ => crate::properties::resolve_group(name)?,
+            });
+        }
+    }
+    entries.sort_by_key(|e| (e.tag, e.id));
+
+    for tag in [AclTag::UserObj, AclTag::GroupObj, AclTag::Other] {
+        if entries.iter().filter(|e| e.tag == tag).count() != 1 {
+            return Err(format!("ACL needs exactly one {:?} entry", tag));
+        }
+    }
+    if entries.windows(2).any(|w| w[0].tag == w[1].tag && w[0].id == w[1].id) {
+        return Err("Duplicate ACL entry".to_string());
+    }
+
+    let has_named = entries.iter().any(|e| e.tag.is_named());
+    let has_mask = entries.iter().any(|e| e.tag == AclTag::Mask);
+    if has_named && !has_mask {
+        // Like setfacl: the mask becomes the union of the group class
+        let bits = entries
+            .iter()
+            .filter(|e| matches!(e.tag, AclTag::User | AclTag::GroupObj | AclTag::Group))
+            .fold(0, |acc, e| acc | e.perms.bits());
+        entries.push(AclEntry {
+            tag: AclTag::Mask,
+            id: None,
+            name: None,
+            perms: AclPerms::from_bits(bits),
+            effective: AclPerms::from_bits(bits),
+        });
+        entries.sort_by_key(|e| (e.tag, e.id));
+    }
+    Ok(entries)
+}
+
+/// Access and default ACLs of a path; files without an ACL get the
+/// equivalent of their mode bits
+#[cfg(unix)]
+pub fn read_acl(path: &Path, names: &mut NameCache) -> Result<Acl, String> {
+    use std::os::unix::fs::MetadataExt;
+    let meta = std::fs::metadata(path).map_err(|e| e.to_string())?;
+
+    let stored = |name: &str| xattr::get_deref(path, name).ok().flatten();
+    let (mut access, extended) = match stored(ACCESS_XATTR) {
+        Some(bytes) => (decode(&bytes)?, true),
+        None => (from_mode(meta.mode()), false),
+    };
+    let mut default = match stored(DEFAULT_XATTR) {
+        Some(bytes) if meta.is_dir() => decode(&bytes)?,
+        _ => Vec::new(),
+    };
+    annotate(&mut access, names);
+    annotate(&mut default, names);
+    Ok(Acl {
+        access,
+        default,
+        extended,
+    })
+}
+
+#[cfg(not(unix))]
+pub fn read_acl(_path: &Path, _names: &mut NameCache) -> Result<Acl, String> {
+    Err("Not supported on this platform".to_string())
+}
+
+#[tauri::command]
+pub fn get_acl(path: String) -> Result<Acl, String> {
+    read_acl(&pathenc::decode(&path), &mut NameCache::default())
+}
+
+/// Replace the access and/or default ACL. An empty `default` list removes
+/// the default ACL; lists that are left out stay as they are.
+#[cfg(unix)]
+#[tauri::command]
+pub fn set_acl(path: String, access: Option<Vec<AclEntry>>, default: Option<Vec<AclEntry>>) -> Result<Acl, String> {
+    let p = pathenc::decode(&path);
+    if let Some(entries) = access {
+        // The kernel also updates the mode bits to match
+        let entries = normalize(entries)?;
+        xattr::set_deref(&p, ACCESS_XATTR, &encode(&entries)).map_err(|e| e.to_string())?;
+    }
+    match default {
+        Some(entries) if entries.is_empty() => match xattr::remove_deref(&p, DEFAULT_XATTR) {
+            Err(e) if e.raw_os_error() != Some(libc::ENODATA) => return Err(e.to_string()),
+            _ => {}
+        },
+        Some(entries) => {
+            if !p.is_dir() {
+                return Err("Only directories can have a default ACL".to_string());
+            }
+            let entries = normalize(entries)?;
+            xattr::set_deref(&p, DEFAULT_XATTR, &encode(&entries)).map_err(|e| e.to_string())?;
+        }
+        None => {}
+    }
+    read_acl(&p, &mut NameCache::default())
+}
+
+#[cfg(not(unix))]
+#[tauri::command]
+pub fn set_acl(_path: String, _access: Option<Vec<AclEntry>>, _default: Option<Vec<AclEntry>>) -> Result<Acl, String> {
+    Err("Not supported on this platform".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(tag: AclTag, id: Option<u32>, bits: u16) -> AclEntry {
+        AclEntry {
+            tag,
+            id,
+            name: None,
+            perms: AclPerms::from_bits(bits),
+            effective: AclPerms::from_bits(bits),
+        }
+    }
+
+    fn summary(entries: &[AclEntry]) -> Vec<(AclTag, Option<u32>, u16)> {
+        entries.iter().map(|e| (e.tag, e.id, e.perms.bits())).collect()
+    }
+
+    #[test]
+    fn encodes_kernel_layout() {
+        let bytes = encode(&from_mode(0o640));
+        assert_eq!(
+            bytes,
+            [
+                2, 0, 0, 0, //
+                0x01, 0, 6, 0, 0xff, 0xff, 0xff, 0xff, //
+                0x04, 0, 4, 0, 0xff, 0xff, 0xff, 0xff, //
+                0x20, 0, 0, 0, 0xff, 0xff, 0xff, 0xff,
+            ]
+        );
+    }
+
+    #[test]
+    fn round_trips() {
+        let entries = vec![
+            entry(AclTag::UserObj, None, 7),
+            entry(AclTag::User, Some(1000), 6),
+            entry(AclTag::GroupObj, None, 5),
+            entry(AclTag::Group, Some(0), 4),
+            entry(AclTag::Mask, None, 7),
+            entry(AclTag::Other, None, 1),
+        ];
+        let decoded = decode(&encode(&entries)).unwrap();
+        assert_eq!(summary(&decoded), summary(&entries));
+    }
+
+    #[test]
+    fn rejects_malformed() {
+        assert!(decode(&[2, 0]).is_err());
+        assert!(decode(&[1, 0, 0, 0]).is_err());
+        assert!(decode(&[2, 0, 0, 0, 1, 0, 6, 0]).is_err());
+        assert!(decode(&[2, 0, 0, 0, 0x40, 0, 6, 0, 0, 0, 0, 0]).is_err());
+    }
+
+    #[test]
+    fn mask_limits_group_class() {
+        let mut entries = vec![
+            entry(AclTag::UserObj, None, 7),
+            entry(AclTag::User, Some(1000), 7),
+            entry(AclTag::GroupObj, None, 6),
+            entry(AclTag::Mask, None, 4),
+            entry(AclTag::Other, None, 7),
+        ];
+        annotate(&mut entries, &mut NameCache::default());
+        let effective: Vec<u16> = entries.iter().map(|e| e.effective.bits()).collect();
+        assert_eq!(effective, [7, 4, 4, 4, 7]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn computes_missing_mask() {
+        let entries = normalize(vec![
+            entry(AclTag::Other, None, 0),
+            entry(AclTag::Group, Some(100), 1),
+            entry(AclTag::UserObj, None, 6),
+            entry(AclTag::User, Some(1000), 2),
+            entry(AclTag::GroupObj, None, 4),
+        ])
+        .unwrap();
+        assert_eq!(
+            summary(&entries),
+            [
+                (AclTag::UserObj, None, 6),
+                (AclTag::User, Some(1000), 2),
+                (AclTag::GroupObj, None, 4),
+                (AclTag::Group, Some(100), 1),
+                (AclTag::Mask, None, 7),
+                (AclTag::Other, None, 0),
+            ]
+        );
+        let decoded = decode(&encode(&entries)).unwrap();
+        assert_eq!(summary(&decoded), summary(&entries));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn rejects_incomplete_sets() {
+        assert!(normalize(vec![entry(AclTag::UserObj, None, 6), entry(AclTag::Other, None, 0)]).is_err());
+        let duplicate = vec![
+            entry(AclTag::UserObj, None, 6),
+            entry(AclTag::User, Some(1), 6),
+            entry(AclTag::User, Some(1), 4),
+            entry(AclTag::GroupObj, None, 4),
+            entry(AclTag::Other, None, 0),
+        ];
+        assert!(normalize(duplicate).is_err());
+    }
+}
@@ -0,0 +1,955 @@
+// Creating and extracting archives. Existing archives are recognised by their
+// magic bytes, so a tarball with a wrong or missing extension still opens.
+// 7z is read in pure Rust; RAR goes through the bundled unrar library.
+use crate::pathenc;
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, Write};
+use std::path::{Component, Path, PathBuf};
+use walkdir::WalkDir;
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveFormat {
+    Zip,
+    Tar,
+    TarGz,
+    TarXz,
+    TarZst,
+    TarBz2,
+}
+
+impl ArchiveFormat {
+    // Used when compress_items is not told the format
+    fn from_file_name(name: &str) -> Self {
+        let name = name.to_ascii_lowercase();
+        [
+            (".tar.gz", ArchiveFormat::TarGz),
+            (".tgz", ArchiveFormat::TarGz),
+            (".tar.xz", ArchiveFormat::TarXz),
+            (".txz", ArchiveFormat::TarXz),
+            (".tar.zst", ArchiveFormat::TarZst),
+            (".tzst", ArchiveFormat::TarZst),
+            (".tar.bz2", ArchiveFormat::TarBz2),
+            (".tbz2", ArchiveFormat::TarBz2),
+            (".tar", ArchiveFormat::Tar),
+        ]
+        .into_iter()
+        .find(|(ext, _)| name.ends_with(ext))
+        .map_or(ArchiveFormat::Zip, |(_, format)| format)
+    }
+
+    fn compression(self) -> Option<Compression> {
+        match self {
+            ArchiveFormat::Zip => None,
+            ArchiveFormat::Tar => Some(Compression::None),
+            ArchiveFormat::TarGz => Some(Compression::Gzip),
+            ArchiveFormat::TarXz => Some(Compression::Xz),
+            ArchiveFormat::TarZst => Some(Compression::Zstd),
+            ArchiveFormat::TarBz2 => Some(Compression::Bzip2),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Compression {
+    None,
+    Gzip,
+    Xz,
+    Zstd,
+    Bzip2,
+}
+
+// What a file holds, judged by its first bytes
+pub(crate) enum Detected {
+    Zip,
+    SevenZ,
+    Rar,
+    Stream(Compression),
+}
+
+pub(crate) fn detect(header: &[u8]) -> Option<Detected> {
+    let magic = [
+        (&b"PK\x03\x04"[..], Detected::Zip),
+        // An empty zip is just the end-of-directory record
+        (b"PK\x05\x06", Detected::Zip),
+        (b"7z\xbc\xaf\x27\x1c", Detected::SevenZ),
+        // RAR 1.5-4.x and RAR 5
+        (b"Rar!\x1a\x07\x00", Detected::Rar),
+        (b"Rar!\x1a\x07\x01\x00", Detected::Rar),
+        (b"\x1f\x8b", Detected::Stream(Compression::Gzip)),
+        (b"\xfd7zXZ\x00", Detected::Stream(Compression::Xz)),
+        (b"\x28\xb5\x2f\xfd", Detected::Stream(Compression::Zstd)),
+        (b"BZh", Detected::Stream(Compression::Bzip2)),
+    ];
+    magic
+        .into_iter()
+        .find(|(m, _)| header.starts_with(m))
+        .map(|(_, detected)| detected)
+        .or_else(|| is_tar(header).then_some(Detected::Stream(Compression::None)))
+}
+
+// ustar and GNU archives carry a magic; old v7 ones only a header checksum
+pub(crate) fn is_tar(block: &[u8]) -> bool {
+    if block.len() < 512 {
+        return false;
+    }
+    if &block[257..262] == b"ustar" {
+        return true;
+    }
+    let stored = std::str::from_utf8(&block[148..156])
+        .ok()
+        .map(|s| s.trim_matches(|c| c == '\0' || c == ' '))
+        .and_then(|s| u32::from_str_radix(s, 8).ok());
+    // The checksum field itself counts as spaces
+    let sum: u32 = block[..512]
+        .iter()
+        .enumerate()
+        .map(|(i, &b)| if (148..156).contains(&i) { b' ' as u32 } else { b as u32 })
+        .sum();
+    stored == Some(sum)
+}
+
+pub(crate) fn decoder<'a>(reader: impl Read + 'a, compression: Compression) -> Result<Box<dyn Read + 'a>, String> {
+    Ok(match compression {
+        Compression::None => Box::new(reader),
+        Compression::Gzip => Box::new(flate2::read::MultiGzDecoder::new(reader)),
+        Compression::Xz => Box::new(xz2::read::XzDecoder::new_multi_decoder(reader)),
+        Compression::Zstd => Box::new(zstd::stream::read::Decoder::new(reader).map_err(|e| e.to_string())?),
+        Compression::Bzip2 => Box::new(bzip2::read::MultiBzDecoder::new(reader)),
+    })
+}
+
+/// How zip entries are compressed. Tarballs are compressed as a whole,
+/// as their format says.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CompressionMethod {
+    Store,
+    #[default]
+    Deflate,
+    Bzip2,
+    Zstd,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct CompressOptions {
+    pub method: CompressionMethod,
+    /// Level for the method or the tarball's compression; None for its default
+    pub level: Option<i64>,
+    /// Keep each file's mode; otherwise files get 0644 and folders 0755
+    pub preserve_permissions: bool,
+    /// Dot files inside the packed folders; selected items always go in
+    pub include_hidden: bool,
+}
+
+impl Default for CompressOptions {
+    fn default() -> Self {
+        CompressOptions {
+            method: CompressionMethod::default(),
+            level: None,
+            preserve_permissions: true,
+            include_hidden: true,
+        }
+    }
+}
+
+// Formats that are compressed already; compressing them again costs time and saves nothing
+const PRECOMPRESSED: &[&str] = &[
+    "jpg", "jpeg", "png", "gif", "webp", "heic", "avif", "mp3", "m4a", "aac", "ogg", "opus", "flac", "mp4", "m4v",
+    "mkv", "webm", "mov", "avi", "zip", "7z", "rar", "gz", "tgz", "xz", "txz", "zst", "bz2", "jar", "apk", "docx",
+    "xlsx", "pptx", "odt", "epub", "woff2",
+];
+
+fn is_precompressed(path: &Path) -> bool {
+    path.extension()
+        .map(|e| e.to_string_lossy().to_ascii_lowercase())
+        .is_some_and(|e| PRECOMPRESSED.contains(&e.as_str()))
+}
+
+fn check_level(level: Option<i64>, range: std::ops::RangeInclusive<i64>) -> Result<Option<i64>, String> {
+    match level {
+        Some(l) if !range.contains(&l) => Err(format!(
+            "Compression level must be between {} and {}",
+            range.start(),
+            range.end()
+        )),
+        _ => Ok(level),
+    }
+}
+
+fn default_mode(is_dir: bool) -> u32 {
+    if is_dir {
+        0o755
+    } else {
+        0o644
+    }
+}
+
+#[cfg(unix)]
+fn file_mode(meta: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode() & 0o7777
+}
+
+#[cfg(not(unix))]
+fn file_mode(meta: &fs::Metadata) -> u32 {
+    default_mode(meta.is_dir())
+}
+
+// What to pack: each path with its name inside the archive, folders before their contents
+fn walk(paths: &[String], output: &Path, include_hidden: bool) -> Vec<(PathBuf, PathBuf)> {
+    let mut items = Vec::new();
+    for path_str in paths {
+        let path = pathenc::decode(path_str);
+        let base = path.parent().unwrap_or(Path::new("")).to_path_buf();
+        // Selected links are packed as links too, not as the folder they point to
+        let entries = WalkDir::new(&path)
+            .follow_root_links(false)
+            .into_iter()
+            .filter_entry(|e| include_hidden || e.depth() == 0 || !e.file_name().to_string_lossy().starts_with('.'))
+            .filter_map(|e| e.ok())
+            // The archive may be written into a folder being packed
+            .filter(|e| e.path() != output);
+        for entry in entries {
+            let name = entry.path().strip_prefix(&base).unwrap_or(entry.path()).to_path_buf();
+            items.push((entry.into_path(), name));
+        }
+    }
+    items
+}
+
+fn create_zip(paths: &[String], output: &Path, options: &CompressOptions) -> Result<(), String> {
+    use zip::write::SimpleFileOptions;
+    use zip::ZipWriter;
+
+    let (method, range) = match options.method {
+        CompressionMethod::Store => (zip::CompressionMethod::Stored, 0..=0),
+        CompressionMethod::Deflate => (zip::CompressionMethod::Deflated, 0..=9),
+        CompressionMethod::Bzip2 => (zip::CompressionMethod::Bzip2, 1..=9),
+        CompressionMethod::Zstd => (zip::CompressionMethod::Zstd, 1..=22),
+    };
+    let level = if method == zip::CompressionMethod::Stored {
+        None
+    } else {
+        check_level(options.level, range)?
+    };
+
+    let file = File::create(output).map_err(|e| e.to_string())?;
+    let mut zip = ZipWriter::new(file);
+    for (path, name) in walk(paths, output, options.include_hidden) {
+        let meta = fs::symlink_metadata(&path).map_err(|e| e.to_string())?;
+        // Zip names always use forward slashes
+        let name = name.iter().map(|c| c.to_string_lossy()).collect::<Vec<_>>().join("/");
+        let mode = if options.preserve_permissions { file_mode(&meta) } else { default_mode(meta.is_dir()) };
+        let entry_options = SimpleFileOptions::default().unix_permissions(mode);
+
+        if meta.is_symlink() {
+            // Stored as links, as in tarballs, whether they point to files or folders
+            let target = fs::read_link(&path).map_err(|e| e.to_string())?;
+            zip.add_symlink(name, target.to_string_lossy(), SimpleFileOptions::default())
+                .map_err(|e| e.to_string())?;
+        } else if meta.is_dir() {
+            zip.add_directory(name, entry_options).map_err(|e| e.to_string())?;
+        } else if meta.is_file() {
+            let entry_options = if is_precompressed(&path) {
+                entry_options.compression_method(zip::CompressionMethod::Stored)
+            } else {
+                entry_options.compression_method(method).compression_level(level)
+            };
+            zip.start_file(name, entry_options.large_file(meta.len() >= u32::MAX as u64))
+                .map_err(|e| e.to_string())?;
+            let mut f = File::open(&path).map_err(|e| e.to_string())?;
+            io::copy(&mut f, &mut zip).map_err(|e| e.to_string())?;
+        }
+    }
+
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+// Symlinks are stored as links; owners and mtimes come along
+fn write_tar<W: Write>(writer: W, paths: &[String], output: &Path, options: &CompressOptions) -> Result<W, String> {
+    let mut builder = tar::Builder::new(writer);
+    for (path, name) in walk(paths, output, options.include_hidden) {
+        let meta = fs::symlink_metadata(&path).map_err(|e| e.to_string())?;
+        let mut header = tar::Header::new_gnu();
+        header.set_metadata_in_mode(&meta, tar::HeaderMode::Complete);
+        if !options.preserve_permissions && !meta.is_symlink() {
+            header.set_mode(default_mode(meta.is_dir()));
+        }
+        if meta.is_symlink() {
+            let target = fs::read_link(&path).map_err(|e| e.to_string())?;
+            builder.append_link(&mut header, &name, target)
+        } else if meta.is_file() {
+            let file = File::open(&path).map_err(|e| e.to_string())?;
+            builder.append_data(&mut header, &name, file)
+        } else {
+            // Folders, and devices or fifos, which have no content
+            header.set_size(0);
+            builder.append_data(&mut header, &name, io::empty())
+        }
+        .map_err(|e| e.to_string())?;
+    }
+    builder.into_inner().map_err(|e| e.to_string())
+}
+
+fn create_tar(paths: &[String], output: &Path, compression: Compression, options: &CompressOptions) -> Result<(), String> {
+    let writer = io::BufWriter::new(File::create(output).map_err(|e| e.to_string())?);
+    let level = |range, default| check_level(options.level, range).map(|l| l.unwrap_or(default));
+    let writer = match compression {
+        Compression::None => write_tar(writer, paths, output, options)?,
+        Compression::Gzip => {
+            let level = flate2::Compression::new(level(0..=9, 6)? as u32);
+            write_tar(flate2::write::GzEncoder::new(writer, level), paths, output, options)?
+                .finish()
+                .map_err(|e| e.to_string())?
+        }
+        Compression::Xz => write_tar(xz2::write::XzEncoder::new(writer, level(0..=9, 6)? as u32), paths, output, options)?
+            .finish()
+            .map_err(|e| e.to_string())?,
+        Compression::Zstd => {
+            let encoder = zstd::Encoder::new(writer, level(1..=22, 3)? as i32).map_err(|e| e.to_string())?;
+            write_tar(encoder, paths, output, options)?.finish().map_err(|e| e.to_string())?
+        }
+        Compression::Bzip2 => {
+            let level = bzip2::Compression::new(level(1..=9, 6)? as u32);
+            write_tar(bzip2::write::BzEncoder::new(writer, level), paths, output, options)?
+                .finish()
+                .map_err(|e| e.to_string())?
+        }
+    };
+    writer.into_inner().map_err(|e| e.to_string())?.sync_all().map_err(|e| e.to_string())
+}
+
+/// Pack files and folders into an archive. Without a `format`, it follows
+/// the output name's extension and falls back to zip. In zips, files that
+/// are compressed already, such as photos and videos, are stored as they are.
+#[tauri::command]
+pub async fn compress_items(
+    paths: Vec<String>,
+    output_path: String,
+    format: Option<ArchiveFormat>,
+    options: Option<CompressOptions>,
+) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let output = pathenc::decode(&output_path);
+        let format = format.unwrap_or_else(|| ArchiveFormat::from_file_name(&output.to_string_lossy()));
+        let options = options.unwrap_or_default();
+        let result = match format.compression() {
+            Some(compression) => create_tar(&paths, &output, compression, &options),
+            None => create_zip(&paths, &output, &options),
+        };
+        if result.is_err() {
+            // Do not leave a truncated archive behind
+            let _ = fs::remove_file(&output);
+        }
+        result.map(|_| output_path)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveErrorKind {
+    /// Not an archive this app can read
+    UnsupportedFormat,
+    /// A known container using a compression method that is not supported
+    UnsupportedMethod,
+    /// Needs a password
+    Encrypted,
+    /// Truncated or damaged, or a checksum did not match
+    Corrupt,
+    /// Reading the archive or writing the output failed
+    Io,
+}
+
+pub(crate) const ENCRYPTED: &str = "The archive is password protected";
+
+/// Why an extraction failed, so the frontend can tell a password-protected
+/// archive from a damaged one
+#[derive(Debug, Serialize, Clone)]
+pub struct ArchiveError {
+    pub kind: ArchiveErrorKind,
+    pub message: String,
+}
+
+impl ArchiveError {
+    pub(crate) fn new(kind: ArchiveErrorKind, message: impl Into<String>) -> Self {
+        ArchiveError { kind, message: message.into() }
+    }
+}
+
+impl std::fmt::Display for ArchiveError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl From<String> for ArchiveError {
+    fn from(message: String) -> Self {
+        ArchiveError::new(ArchiveErrorKind::Io, message)
+    }
+}
+
+impl From<io::Error> for ArchiveError {
+    fn from(e: io::Error) -> Self {
+        // Decoders report bad input as InvalidData/InvalidInput, short files as UnexpectedEof
+        let kind = match e.kind() {
+            io::ErrorKind::InvalidData | io::ErrorKind::InvalidInput | io::ErrorKind::UnexpectedEof => {
+                ArchiveErrorKind::Corrupt
+            }
+            _ => ArchiveErrorKind::Io,
+        };
+        ArchiveError::new(kind, e.to_string())
+    }
+}
+
+impl From<zip::result::ZipError> for ArchiveError {
+    fn from(e: zip::result::ZipError) -> Self {
+        use zip::result::ZipError;
+        match e {
+            ZipError::Io(e) => e.into(),
+            ZipError::UnsupportedArchive(ZipError::PASSWORD_REQUIRED) | ZipError::InvalidPassword => {
+                ArchiveError::new(ArchiveErrorKind::Encrypted, ENCRYPTED)
+            }
+            ZipError::UnsupportedArchive(msg) => ArchiveError::new(ArchiveErrorKind::UnsupportedMethod, msg),
+            e => ArchiveError::new(ArchiveErrorKind::Corrupt, e.to_string()),
+        }
+    }
+}
+
+impl From<sevenz_rust::Error> for ArchiveError {
+    fn from(e: sevenz_rust::Error) -> Self {
+        use sevenz_rust::Error;
+        match e {
+            Error::Io(e, _) | Error::FileOpen(e, _) => e.into(),
+            // Built without AES support, so encrypted archives surface as an unknown method
+            Error::PasswordRequired | Error::MaybeBadPassword(_) => {
+                ArchiveError::new(ArchiveErrorKind::Encrypted, ENCRYPTED)
+            }
+            Error::UnsupportedCompressionMethod(method) if method.starts_with("AES") => {
+                ArchiveError::new(ArchiveErrorKind::Encrypted, ENCRYPTED)
+            }
+            Error::UnsupportedCompressionMethod(method) => {
+                ArchiveError::new(ArchiveErrorKind::UnsupportedMethod, format!("Unsupported compression method: {}", method))
+            }
+            Error::UnsupportedVersion { .. } | Error::ExternalUnsupported | Error::Unsupported(_) | Error::MaxMemLimited { .. } => {
+                ArchiveError::new(ArchiveErrorKind::UnsupportedMethod, e.to_string())
+            }
+            e => ArchiveError::new(ArchiveErrorKind::Corrupt, e.to_string()),
+        }
+    }
+}
+
+impl From<unrar::error::UnrarError> for ArchiveError {
+    fn from(e: unrar::error::UnrarError) -> Self {
+        use unrar::error::Code;
+        let kind = match e.code {
+            Code::MissingPassword | Code::BadPassword => ArchiveErrorKind::Encrypted,
+            Code::BadData | Code::BadArchive => ArchiveErrorKind::Corrupt,
+            Code::UnknownFormat => ArchiveErrorKind::UnsupportedMethod,
+            _ => ArchiveErrorKind::Io,
+        };
+        let message = match kind {
+            ArchiveErrorKind::Encrypted => ENCRYPTED.to_string(),
+            _ => e.to_string(),
+        };
+        ArchiveError::new(kind, message)
+    }
+}
+
+fn extract_zip(file: File, output_root: &Path) -> Result<(), ArchiveError> {
+    let mut archive = zip::ZipArchive::new(file)?;
+    fs::create_dir_all(output_root)?;
+
+    for i in 0..archive.len() {
+        let mut file = archive.by_index(i)?;
+        let relative = file.mangled_name();
+
+        let outpath = if file.is_dir() {
+            entry_dir(output_root, &relative)?
+        } else {
+            entry_path(output_root, &relative)?
+        };
+        // Below a link that leads out of output_root
+        let Some(outpath) = outpath else { continue };
+        if !file.is_dir() {
+            #[cfg(unix)]
+            if file.unix_mode().is_some_and(is_symlink_mode) {
+                write_symlink(&mut file, output_root, &outpath)?;
+                continue;
+            }
+            let mut outfile = File::create(&outpath)?;
+            io::copy(&mut file, &mut outfile)?;
+        }
+
+        // Set permissions on Unix
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            if let Some(mode) = file.unix_mode() {
+                fs::set_permissions(&outpath, fs::Permissions::from_mode(mode)).ok();
+            }
+        }
+    }
+    Ok(())
+}
+
+fn unpack_tar(reader: impl Read, output_root: &Path) -> Result<(), ArchiveError> {
+    let mut archive = tar::Archive::new(reader);
+    archive.set_preserve_permissions(true);
+    archive.set_preserve_mtime(true);
+    // Only root may hand files to other users
+    #[cfg(unix)]
+    archive.set_preserve_ownerships(unsafe { libc::geteuid() } == 0);
+    // Entries that would land outside output_root are skipped
+    Ok(archive.unpack(output_root)?)
+}
+
+// A compressed stream is either a tarball or a single compressed file such as notes.txt.gz
+fn extract_stream(file: File, compression: Compression, archive: &Path, output_root: &Path) -> Result<(), ArchiveError> {
+    let mut reader = decoder(file, compression)?;
+    let mut head = Vec::with_capacity(512);
+    (&mut reader).take(512).read_to_end(&mut head)?;
+    let tarball = is_tar(&head);
+    let mut reader = io::Cursor::new(head).chain(reader);
+    if tarball {
+        return unpack_tar(reader, output_root);
+    }
+
+    let name = archive.file_stem().ok_or_else(|| "Invalid file name".to_string())?;
+    let mut out = File::create(output_root.join(name))?;
+    io::copy(&mut reader, &mut out)?;
+    Ok(())
+}
+
+// Resolve an entry name ("a/b/../c", either slash) below the output root.
+// Returns None for names that are empty or climb out of it.
+pub(crate) fn relative_entry_path(name: &str) -> Option<PathBuf> {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in name.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            // Drive letters such as C: only appear in absolute Windows paths
+            p if p.ends_with(':') => return None,
+            p => parts.push(p),
+        }
+    }
+    let path: PathBuf = parts.into_iter().collect();
+    let normal = path.components().all(|c| matches!(c, Component::Normal(_)));
+    (normal && path.components().next().is_some()).then_some(path)
+}
+
+// 7z stores the unix mode in the high half of the attributes when this bit is set
+const SEVENZ_UNIX_EXTENSION: u32 = 0x8000;
+// File type bits of a stored unix mode
+const MODE_TYPE_MASK: u32 = 0o170000;
+const MODE_SYMLINK: u32 = 0o120000;
+
+pub(crate) fn sevenz_unix_mode(entry: &sevenz_rust::SevenZArchiveEntry) -> Option<u32> {
+    let attributes = entry.windows_attributes();
+    (attributes & SEVENZ_UNIX_EXTENSION != 0).then_some(attributes >> 16)
+}
+
+pub(crate) fn is_symlink_mode(mode: u32) -> bool {
+    mode & MODE_TYPE_MASK == MODE_SYMLINK
+}
+
+// Create the folder `relative` below `root` one component at a time and return
+// where it really is. Links already there, such as ones made by earlier
+// entries, are followed only while they resolve inside the root, so a chain of
+// them cannot carry later entries out of it; None when one would.
+fn entry_dir(root: &Path, relative: &Path) -> Result<Option<PathBuf>, ArchiveError> {
+    let root = root.canonicalize()?;
+    let mut dir = root.clone();
+    for component in relative.components() {
+        let next = dir.join(component);
+        dir = match fs::symlink_metadata(&next) {
+            Ok(meta) if meta.is_symlink() => match next.canonicalize() {
+                Ok(real) if real.starts_with(&root) && real.is_dir() => real,
+                _ => return Ok(None),
+            },
+            Ok(_) => next,
+            Err(_) => {
+                fs::create_dir(&next)?;
+                next
+            }
+        };
+    }
+    Ok(Some(dir))
+}
+
+// Where a file entry really goes, with its folders created; None outside the root
+fn entry_path(root: &Path, relative: &Path) -> Result<Option<PathBuf>, ArchiveError> {
+    let (Some(parent), Some(name)) = (relative.parent(), relative.file_name()) else {
+        return Ok(None);
+    };
+    let Some(dir) = entry_dir(root, parent)? else {
+        return Ok(None);
+    };
+    let path = dir.join(name);
+    // A link already there is replaced rather than written through
+    if fs::symlink_metadata(&path).is_ok_and(|m| m.is_symlink()) {
+        fs::remove_file(&path)?;
+    }
+    Ok(Some(path))
+}
+
+// Zip and 7z store a link's target as its content. `outpath` comes from
+// `entry_path`. Only relative targets that stay inside output_root from where
+// the link really is are kept, and ".." may only lead the target: after a
+// folder name it would climb from wherever a link of that name points.
+#[cfg(unix)]
+fn write_symlink(reader: &mut dyn Read, output_root: &Path, outpath: &Path) -> Result<(), ArchiveError> {
+    let mut target = String::new();
+    reader.read_to_string(&mut target)?;
+    let parts: Vec<&str> = target.split('/').filter(|p| !p.is_empty() && *p != ".").collect();
+    let ups = parts.iter().take_while(|p| **p == "..").count();
+    let root = output_root.canonicalize()?;
+    let depth = outpath
+        .parent()
+        .and_then(|p| p.strip_prefix(&root).ok())
+        .map(|p| p.components().count());
+    let inside = !target.starts_with('/')
+        && !parts.is_empty()
+        && !parts[ups..].contains(&"..")
+        && depth.is_some_and(|depth| ups <= depth);
+    if inside {
+        std::os::unix::fs::symlink(&target, outpath)?;
+    }
+    Ok(())
+}
+
+fn write_7z_entry(entry: &sevenz_rust::SevenZArchiveEntry, reader: &mut dyn Read, output_root: &Path) -> Result<(), ArchiveError> {
+    let Some(relative) = relative_entry_path(entry.name()) else {
+        // Drain it anyway; a solid block has to be read in order
+        io::copy(reader, &mut io::sink())?;
+        return Ok(());
+    };
+    if entry.is_directory() {
+        entry_dir(output_root, &relative)?;
+        return Ok(());
+    }
+    let Some(outpath) = entry_path(output_root, &relative)? else {
+        io::copy(reader, &mut io::sink())?;
+        return Ok(());
+    };
+
+    let mode = sevenz_unix_mode(entry);
+    #[cfg(unix)]
+    if mode.is_some_and(is_symlink_mode) {
+        return write_symlink(reader, output_root, &outpath);
+    }
+
+    let mut out = File::create(&outpath)?;
+    io::copy(reader, &mut out)?;
+    if entry.has_last_modified_date {
+        let secs = entry.last_modified_date().to_unix_time();
+        if let Ok(secs) = u64::try_from(secs) {
+            out.set_modified(std::time::UNIX_EPOCH + std::time::Duration::from_secs(secs)).ok();
+        }
+    }
+    #[cfg(unix)]
+    if let Some(mode) = mode {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&outpath, fs::Permissions::from_mode(mode & 0o7777)).ok();
+    }
+    Ok(())
+}
+
+fn extract_7z(file: File, output_root: &Path) -> Result<(), ArchiveError> {
+    // The callback can only return the library's error type, so ours waits here
+    let mut failure = None;
+    let result = sevenz_rust::decompress_with_extract_fn(file, output_root, |entry, reader, _| {
+        match write_7z_entry(entry, reader, output_root) {
+            Ok(()) => Ok(true),
+            Err(e) => {
+                failure = Some(e);
+                Ok(false)
+            }
+        }
+    });
+    match failure {
+        Some(e) => Err(e),
+        None => Ok(result?),
+    }
+}
+
+fn extract_rar(archive: &Path, output_root: &Path) -> Result<(), ArchiveError> {
+    let mut rar = unrar::Archive::new(archive).open_for_processing()?;
+    while let Some(header) = rar.read_header()? {
+        let entry = header.entry();
+        rar = if entry.is_directory() {
+            // unrar creates parents of files itself but not empty folders
+            if let Some(relative) = relative_entry_path(&entry.filename.to_string_lossy()) {
+                fs::create_dir_all(output_root.join(relative))?;
+            }
+            header.skip()?
+        } else {
+            header.extract_with_base(output_root)?
+        };
+    }
+    Ok(())
+}
+
+/// Extract a zip, 7z, rar, tar or compressed tar archive into `output_dir`.
+/// Errors carry a kind so encrypted and damaged archives can be told apart.
+#[tauri::command]
+pub async fn extract_archive(archive_path: String, output_dir: String) -> Result<String, ArchiveError> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<String, ArchiveError> {
+        let path = pathenc::decode(&archive_path);
+        let mut file = File::open(&path)?;
+        let mut header = Vec::with_capacity(512);
+        (&mut file).take(512).read_to_end(&mut header)?;
+        file.rewind()?;
+
+        let output_root = pathenc::decode(&output_dir);
+        let detected = detect(&header)
+            .ok_or_else(|| ArchiveError::new(ArchiveErrorKind::UnsupportedFormat, "Unsupported archive format"))?;
+        fs::create_dir_all(&output_root)?;
+        match detected {
+            Detected::Zip => extract_zip(file, &output_root)?,
+            Detected::SevenZ => extract_7z(file, &output_root)?,
+            Detected::Rar => extract_rar(&path, &output_root)?,
+            Detected::Stream(compression) => extract_stream(file, compression, &path, &output_root)?,
+        }
+
+        Ok(output_dir)
+    })
+    .await
+    .map_err(|e| ArchiveError::from(e.to_string()))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn entry_paths_stay_below_the_root() {
+        let cases = [
+            ("a/b/../c", Some("a/c")),
+            ("./a//b/", Some("a/b")),
+            ("win\\path\\file.txt", Some("win/path/file.txt")),
+            ("mixed/dir\\file", Some("mixed/dir/file")),
+            // Leading slashes are dropped, as tar does
+            ("/etc/passwd", Some("etc/passwd")),
+            ("\\\\server\\share\\file", Some("server/share/file")),
+            ("a/..", None),
+            ("", None),
+            (".", None),
+            ("..", None),
+            ("../escape.txt", None),
+            ("a/../../escape.txt", None),
+            ("a\\..\\..\\escape.txt", None),
+            ("C:\\Windows\\system.ini", None),
+            ("C:/Windows/system.ini", None),
+            ("dir/D:/x", None),
+        ];
+        for (name, expected) in cases {
+            assert_eq!(relative_entry_path(name), expected.map(PathBuf::from), "{:?}", name);
+        }
+    }
+
+    fn tar_block(name: &str) -> Vec<u8> {
+        let mut block = vec![0u8; 512];
+        block[..name.len()].copy_from_slice(name.as_bytes());
+        block[100..107].copy_from_slice(b"0000644");
+        block[124..135].copy_from_slice(b"00000000005");
+        block[136..147].copy_from_slice(b"14000000000");
+        block[148..156].fill(b' ');
+        let sum: u32 = block.iter().map(|&b| b as u32).sum();
+        block[148..155].copy_from_slice(format!("{:06o}\0", sum).as_bytes());
+        block
+    }
+
+    #[test]
+    fn recognises_v7_tar_by_checksum() {
+        let block = tar_block("notes.txt");
+        assert!(is_tar(&block));
+        assert!(matches!(detect(&block), Some(Detected::Stream(Compression::None))));
+
+        let mut damaged = block.clone();
+        damaged[0] ^= 1;
+        assert!(!is_tar(&damaged));
+        assert!(!is_tar(&block[..511]));
+        assert!(!is_tar(&[0u8; 512]));
+
+        let mut ustar = vec![0u8; 512];
+        ustar[257..263].copy_from_slice(b"ustar\0");
+        assert!(is_tar(&ustar));
+    }
+
+    #[test]
+    fn detects_each_signature() {
+        let check = |header: &[u8], expected: fn(&Detected) -> bool| {
+            let detected = detect(header);
+            assert!(detected.as_ref().is_some_and(expected), "{:?}", header);
+        };
+        check(b"PK\x03\x04rest", |d| matches!(d, Detected::Zip));
+        check(b"PK\x05\x06", |d| matches!(d, Detected::Zip));
+        check(b"7z\xbc\xaf\x27\x1c\x00\x04", |d| matches!(d, Detected::SevenZ));
+        check(b"Rar!\x1a\x07\x00", |d| matches!(d, Detected::Rar));
+        check(b"Rar!\x1a\x07\x01\x00", |d| matches!(d, Detected::Rar));
+        check(b"\x1f\x8b\x08", |d| matches!(d, Detected::Stream(Compression::Gzip)));
+        check(b"\xfd7zXZ\x00\x00", |d| matches!(d, Detected::Stream(Compression::Xz)));
+        check(b"\x28\xb5\x2f\xfd", |d| matches!(d, Detected::Stream(Compression::Zstd)));
+        check(b"BZh91AY", |d| matches!(d, Detected::Stream(Compression::Bzip2)));
+        assert!(detect(b"hello world").is_none());
+        assert!(detect(b"Rar!\x1a\x07\x02").is_none());
+        assert!(detect(b"").is_none());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn extracts_7z_symlinks_only_inside_the_root() {
+        use sevenz_rust::{SevenZArchiveEntry, SevenZWriter};
+        use std::os::unix::fs::PermissionsExt;
+
+        fn entry(name: &str, mode: u32) -> SevenZArchiveEntry {
+            let mut entry = SevenZArchiveEntry::new();
+            entry.name = name.to_string();
+            entry.has_stream = true;
+            entry.has_windows_attributes = true;
+            entry.windows_attributes = SEVENZ_UNIX_EXTENSION | (mode << 16);
+            entry
+        }
+
+        let tmp = tempfile::tempdir().unwrap();
+        let archive = tmp.path().join("links.7z");
+        let mut writer = SevenZWriter::create(&archive).unwrap();
+        let members: [(&str, u32, &[u8]); 7] = [
+            ("top.txt", 0o100644, b"top"),
+            ("dir/run.sh", 0o100755, b"#!/bin/sh\n"),
+            ("dir/sibling", 0o120777, b"run.sh"),
+            ("dir/up", 0o120777, b"../top.txt"),
+            ("dir/escape", 0o120777, b"../../outside"),
+            ("dir/absolute", 0o120777, b"/etc/passwd"),
+            ("../outside", 0o100644, b"bad"),
+        ];
+        for (name, mode, content) in members {
+            writer.push_archive_entry(entry(name, mode), Some(content)).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let root = tmp.path().join("out");
+        extract_7z(File::open(&archive).unwrap(), &root).unwrap();
+
+        assert_eq!(fs::read_link(root.join("dir/sibling")).unwrap(), Path::new("run.sh"));
+        assert_eq!(fs::read_link(root.join("dir/up")).unwrap(), Path::new("../top.txt"));
+        assert_eq!(fs::read_to_string(root.join("dir/up")).unwrap(), "top");
+        assert!(fs::symlink_metadata(root.join("dir/escape")).is_err());
+        assert!(fs::symlink_metadata(root.join("dir/absolute")).is_err());
+        assert!(!tmp.path().join("outside").exists());
+        let mode = fs::metadata(root.join("dir/run.sh")).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+    }
+
+    // Every file named `payload` below `dir`
+    #[cfg(unix)]
+    fn payloads(dir: &Path) -> Vec<PathBuf> {
+        walkdir::WalkDir::new(dir)
+            .into_iter()
+            .flatten()
+            .filter(|e| e.file_name() == "payload")
+            .map(|e| e.into_path())
+            .collect()
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn extracts_7z_chained_links_only_inside_the_root() {
+        use sevenz_rust::{SevenZArchiveEntry, SevenZWriter};
+
+        let tmp = tempfile::tempdir().unwrap();
+        let archive = tmp.path().join("chain.7z");
+        let mut writer = SevenZWriter::create(&archive).unwrap();
+        // l reads as c and does land there; x reads as z but lands two levels above the root
+        let members: [(&str, u32, &[u8]); 4] = [
+            ("c/keep", 0o100644, b""),
+            ("a/b/l", 0o120777, b"../../c"),
+            ("a/b/l/x", 0o120777, b"../../../z"),
+            ("a/b/l/x/payload", 0o100644, b"bad"),
+        ];
+        for (name, mode, content) in members {
+            let mut entry = SevenZArchiveEntry::new();
+            entry.name = name.to_string();
+            entry.has_stream = true;
+            entry.has_windows_attributes = true;
+            entry.windows_attributes = SEVENZ_UNIX_EXTENSION | (mode << 16);
+            writer.push_archive_entry(entry, Some(content)).unwrap();
+        }
+        writer.finish().unwrap();
+
+        let root = tmp.path().join("one/two/out");
+        fs::create_dir_all(&root).unwrap();
+        extract_7z(File::open(&archive).unwrap(), &root).unwrap();
+
+        assert_eq!(fs::read_link(root.join("a/b/l")).unwrap(), Path::new("../../c"));
+        assert!(fs::symlink_metadata(root.join("c/x")).unwrap().is_dir());
+        assert_eq!(payloads(tmp.path()), [root.join("c/x/payload")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn extracts_zip_chained_links_only_inside_the_root() {
+        use zip::write::SimpleFileOptions;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let archive = tmp.path().join("chain.zip");
+        let mut zip = zip::ZipWriter::new(File::create(&archive).unwrap());
+        let options = SimpleFileOptions::default();
+        zip.add_directory("c", options).unwrap();
+        // l reads as c and does land there; x reads as z but lands two levels above the root
+        zip.add_symlink("a/b/l", "../../c", options).unwrap();
+        zip.add_symlink("a/b/l/x", "../../../z", options).unwrap();
+        zip.start_file("a/b/l/x/payload", options).unwrap();
+        zip.write_all(b"bad").unwrap();
+        zip.finish().unwrap();
+
+        let root = tmp.path().join("one/two/out");
+        fs::create_dir_all(&root).unwrap();
+        extract_zip(File::open(&archive).unwrap(), &root).unwrap();
+
+        assert_eq!(fs::read_link(root.join("a/b/l")).unwrap(), Path::new("../../c"));
+        assert!(fs::symlink_metadata(root.join("c/x")).unwrap().is_dir());
+        assert_eq!(payloads(tmp.path()), [root.join("c/x/payload")]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn packs_symlinked_folders_as_links() {
+        use std::os::unix::fs::symlink;
+
+        let tmp = tempfile::tempdir().unwrap();
+        let src = tmp.path().join("src");
+        fs::create_dir_all(src.join("real")).unwrap();
+        fs::write(src.join("real/file.txt"), "data").unwrap();
+        symlink("real", src.join("linked")).unwrap();
+        symlink("src/real", tmp.path().join("selected")).unwrap();
+        let paths = [src.to_string_lossy().to_string(), tmp.path().join("selected").to_string_lossy().to_string()];
+
+        let zip = tmp.path().join("out.zip");
+        create_zip(&paths, &zip, &CompressOptions::default()).unwrap();
+        let tar = tmp.path().join("out.tar");
+        create_tar(&paths, &tar, Compression::None, &CompressOptions::default()).unwrap();
+
+        for (archive, root) in [(&zip, tmp.path().join("from_zip")), (&tar, tmp.path().join("from_tar"))] {
+            let file = File::open(archive).unwrap();
+            if archive == &zip {
+                extract_zip(file, &root).unwrap();
+            } else {
+                unpack_tar(file, &root).unwrap();
+            }
+            assert_eq!(fs::read_to_string(root.join("src/real/file.txt")).unwrap(), "data");
+            assert_eq!(fs::read_link(root.join("src/linked")).unwrap(), Path::new("real"));
+            assert_eq!(fs::read_to_string(root.join("src/linked/file.txt")).unwrap(), "data");
+            assert_eq!(fs::read_link(root.join("selected")).unwrap(), Path::new("src/real"));
+        }
+    }
+}
@@ -0,0 +1,494 @@
+// Browsing archives as if they were folders. A path such as
+// /home/me/backup.zip/docs/old.tar.gz/notes runs from a file on disk into the
+// archive and on into archives nested in it, which are read into memory.
+// Recently opened archives stay indexed so moving between their folders does
+// not rescan them.
+use crate::archive::{self, ArchiveError, ArchiveErrorKind, Compression, Detected};
+use crate::commands::{self, DirContents, FileEntry};
+use crate::listing::{self, ListOptions};
+use crate::metadata::Timestamp;
+use crate::pathenc;
+use std::collections::BTreeMap;
+use std::fs::{self, File};
+use std::io::{self, Cursor, Read, Seek};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::SystemTime;
+
+// Nested archives are read into memory up to this size
+const NESTED_LIMIT: u64 = 256 * 1024 * 1024;
+const CACHED_ARCHIVES: usize = 8;
+// Nested archives held by the cache, in bytes; the least recently used go first
+const CACHED_BYTES: u64 = 512 * 1024 * 1024;
+// The unrar library only extracts whole members, so a RAR member larger than
+// the bytes asked for is read only up to this size and refused beyond it
+const RAR_MEMBER_LIMIT: u64 = 64 * 1024 * 1024;
+
+trait ReadSeek: Read + Seek {}
+impl<T: Read + Seek> ReadSeek for T {}
+
+#[derive(Clone)]
+enum Source {
+    File(PathBuf),
+    Bytes(Arc<[u8]>),
+}
+
+impl Source {
+    fn open(&self) -> Result<Box<dyn ReadSeek>, ArchiveError> {
+        Ok(match self {
+            Source::File(path) => Box::new(File::open(path)?),
+            Source::Bytes(bytes) => Box::new(Cursor::new(bytes.clone())),
+        })
+    }
+
+    // Memory kept alive by holding on to this source
+    fn held_bytes(&self) -> u64 {
+        match self {
+            Source::File(_) => 0,
+            Source::Bytes(bytes) => bytes.len() as u64,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Kind {
+    Zip,
+    SevenZ,
+    Rar,
+    Tar(Compression),
+    // A single compressed file such as notes.txt.gz
+    Single(Compression),
+}
+
+#[derive(Debug, Clone)]
+struct Member {
+    /// '/'-separated path inside the archive
+    path: String,
+    /// Position among the archive's entries
+    index: usize,
+    is_dir: bool,
+    is_symlink: bool,
+    size: u64,
+    modified: Option<i64>,
+}
+
+#[derive(Clone)]
+struct Archive {
+    source: Source,
+    kind: Kind,
+    members: Arc<Vec<Member>>,
+}
+
+// Entry names use either slash; members that would escape the archive are left out
+fn normalize(name: &str) -> Option<String> {
+    let path = archive::relative_entry_path(name)?;
+    Some(path.iter().map(|c| c.to_string_lossy()).collect::<Vec<_>>().join("/"))
+}
+
+fn not_found(path: &str) -> ArchiveError {
+    ArchiveError::new(ArchiveErrorKind::Io, format!("Not found in the archive: {}", path))
+}
+
+// Zip stores local time without a zone
+fn zip_time(t: zip::DateTime) -> Option<i64> {
+    chrono::NaiveDate::from_ymd_opt(t.year().into(), t.month().into(), t.day().into())?
+        .and_hms_opt(t.hour().into(), t.minute().into(), t.second().into())?
+        .and_local_timezone(chrono::Local)
+        .earliest()
+        .map(|d| d.timestamp())
+}
+
+fn zip_members(reader: Box<dyn ReadSeek>) -> Result<Vec<Member>, ArchiveError> {
+    let mut zip = zip::ZipArchive::new(reader)?;
+    let mut members = Vec::new();
+    for index in 0..zip.len() {
+        // Raw access reads the metadata of encrypted entries too
+        let file = zip.by_index_raw(index)?;
+        let Some(path) = normalize(file.name()) else { continue };
+        members.push(Member {
+            path,
+            index,
+            is_dir: file.is_dir(),
+            is_symlink: file.is_symlink(),
+            size: file.size(),
+            modified: file.last_modified().and_then(zip_time),
+        });
+    }
+    Ok(members)
+}
+
+fn tar_members(reader: impl Read) -> Result<Vec<Member>, ArchiveError> {
+    let mut tar = tar::Archive::new(reader);
+    let mut members = Vec::new();
+    for (index, entry) in tar.entries()?.enumerate() {
+        let entry = entry?;
+        let Some(path) = normalize(&entry.path()?.to_string_lossy()) else { continue };
+        let header = entry.header();
+        members.push(Member {
+            path,
+            index,
+            is_dir: header.entry_type().is_dir(),
+            is_symlink: header.entry_type().is_symlink(),
+            size: header.size()?,
+            modified: header.mtime().ok().and_then(|t| i64::try_from(t).ok()),
+        });
+    }
+    Ok(members)
+}
+
+fn sevenz_reader(mut reader: Box<dyn ReadSeek>) -> Result<sevenz_rust::SevenZReader<Box<dyn ReadSeek>>, ArchiveError> {
+    let len = reader.seek(io::SeekFrom::End(0))?;
+    reader.rewind()?;
+    Ok(sevenz_rust::SevenZReader::new(reader, len, sevenz_rust::Password::empty())?)
+}
+
+fn sevenz_members(reader: Box<dyn ReadSeek>) -> Result<Vec<Member>, ArchiveError> {
+    let sevenz = sevenz_reader(reader)?;
+    Ok(sevenz
+        .archive()
+        .files
+        .iter()
+        .enumerate()
+        .filter_map(|(index, file)| {
+            Some(Member {
+                path: normalize(file.name())?,
+                index,
+                is_dir: file.is_directory(),
+                is_symlink: archive::sevenz_unix_mode(file).is_some_and(archive::is_symlink_mode),
+                size: file.size(),
+                modified: file.has_last_modified_date.then(|| file.last_modified_date().to_unix_time()),
+            })
+        })
+        .collect())
+}
+
+fn rar_members(path: &Path) -> Result<Vec<Member>, ArchiveError> {
+    let mut members = Vec::new();
+    for (index, header) in unrar::Archive::new(path).open_for_listing()?.enumerate() {
+        let header = header?;
+        let Some(path) = normalize(&header.filename.to_string_lossy()) else { continue };
+        members.push(Member {
+            path,
+            index,
+            is_dir: header.is_directory(),
+            is_symlink: false,
+            size: header.unpacked_size,
+            modified: None,
+        });
+    }
+    Ok(members)
+}
+
+fn open_archive(source: Source, name: &str) -> Result<Archive, ArchiveError> {
+    let mut reader = source.open()?;
+    let mut header = Vec::with_capacity(512);
+    (&mut reader).take(512).read_to_end(&mut header)?;
+    reader.rewind()?;
+
+    let detected = archive::detect(&header).ok_or_else(|| {
+        ArchiveError::new(ArchiveErrorKind::UnsupportedFormat, format!("{} is not a supported archive", name))
+    })?;
+    let (kind, members) = match detected {
+        Detected::Zip => (Kind::Zip, zip_members(reader)?),
+        Detected::SevenZ => (Kind::SevenZ, sevenz_members(reader)?),
+        Detected::Rar => match &source {
+            Source::File(path) => (Kind::Rar, rar_members(path)?),
+            // The unrar library only reads from files
+            Source::Bytes(_) => {
+                return Err(ArchiveError::new(
+                    ArchiveErrorKind::UnsupportedFormat,
+                    "RAR archives inside other archives cannot be browsed",
+                ))
+            }
+        },
+        Detected::Stream(compression) => {
+            let mut stream = archive::decoder(reader, compression)?;
+            let mut head = Vec::with_capacity(512);
+            (&mut stream).take(512).read_to_end(&mut head)?;
+            if archive::is_tar(&head) {
+                (Kind::Tar(compression), tar_members(Cursor::new(head).chain(stream))?)
+            } else {
+                let stem = Path::new(name).file_stem().map(|s| s.to_string_lossy().to_string());
+                let member = Member {
+                    path: stem.unwrap_or_else(|| name.to_string()),
+                    index: 0,
+                    is_dir: false,
+                    is_symlink: false,
+                    // Only known after decompressing it all
+                    size: 0,
+                    modified: None,
+                };
+                (Kind::Single(compression), vec![member])
+            }
+        }
+    };
+    Ok(Archive {
+        source,
+        kind,
+        members: Arc::new(members),
+    })
+}
+
+fn read_member(archive: &Archive, member: &Member, limit: u64) -> Result<Vec<u8>, ArchiveError> {
+    let mut out = Vec::new();
+    match archive.kind {
+        Kind::Zip => {
+            let mut zip = zip::ZipArchive::new(archive.source.open()?)?;
+            zip.by_index(member.index)?.take(limit).read_to_end(&mut out)?;
+        }
+        Kind::Tar(compression) => {
+            let mut tar = tar::Archive::new(archive::decoder(archive.source.open()?, compression)?);
+            let entry = tar.entries()?.nth(member.index).ok_or_else(|| not_found(&member.path))??;
+            entry.take(limit).read_to_end(&mut out)?;
+        }
+        Kind::Single(compression) => {
+            archive::decoder(archive.source.open()?, compression)?.take(limit).read_to_end(&mut out)?;
+        }
+        Kind::SevenZ => {
+            let mut sevenz = sevenz_reader(archive.source.open()?)?;
+            let mut found = None;
+            sevenz.for_each_entries(|entry, data| {
+                if normalize(entry.name()).as_deref() != Some(member.path.as_str()) {
+                    // Entries of a solid block have to be read through in order
+                    io::copy(data, &mut io::sink())?;
+                    return Ok(true);
+                }
+                found = Some(data.take(limit).read_to_end(&mut out));
+                Ok(false)
+            })?;
+            found.ok_or_else(|| not_found(&member.path))??;
+        }
+        Kind::Rar => {
+            let Source::File(path) = &archive.source else {
+                return Err(not_found(&member.path));
+            };
+            let mut rar = unrar::Archive::new(path).open_for_processing()?;
+            loop {
+                let Some(header) = rar.read_header()? else {
+                    return Err(not_found(&member.path));
+                };
+                let entry = header.entry();
+                if normalize(&entry.filename.to_string_lossy()).as_deref() == Some(member.path.as_str()) {
+                    if entry.unpacked_size > limit.max(RAR_MEMBER_LIMIT) {
+                        return Err(ArchiveError::from(format!(
+                            "{} is too large to read from a RAR archive",
+                            member.path
+                        )));
+                    }
+                    out = header.read()?.0;
+                    out.truncate(usize::try_from(limit).unwrap_or(usize::MAX));
+                    break;
+                }
+                rar = header.skip()?;
+            }
+        }
+    }
+    Ok(out)
+}
+
+struct Cached {
+    key: PathBuf,
+    stamp: Option<(SystemTime, u64)>,
+    archive: Archive,
+}
+
+fn cache() -> &'static Mutex<Vec<Cached>> {
+    static CACHE: OnceLock<Mutex<Vec<Cached>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+// Nested archives carry the stamp of the file on disk, so rewriting it drops them all
+fn stamp(path: &Path) -> Option<(SystemTime, u64)> {
+    fs::metadata(path).ok().and_then(|m| Some((m.modified().ok()?, m.len())))
+}
+
+fn open_cached(
+    key: &Path,
+    stamp: Option<(SystemTime, u64)>,
+    open: impl FnOnce() -> Result<Archive, ArchiveError>,
+) -> Result<Archive, ArchiveError> {
+    if let Ok(mut cache) = cache().lock() {
+        if let Some(i) = cache.iter().position(|c| c.key == key && c.stamp == stamp) {
+            // Most recently used last
+            let hit = cache.remove(i);
+            let archive = hit.archive.clone();
+            cache.push(hit);
+            return Ok(archive);
+        }
+    }
+    let archive = open()?;
+    if let Ok(mut cache) = cache().lock() {
+        cache.retain(|c| c.key != key);
+        cache.push(Cached {
+            key: key.to_path_buf(),
+            stamp,
+            archive: archive.clone(),
+        });
+        let mut held: u64 = cache.iter().map(|c| c.archive.source.held_bytes()).sum();
+        // The archive just opened always stays, even when it alone is over the budget
+        while cache.len() > 1 && (cache.len() > CACHED_ARCHIVES || held > CACHED_BYTES) {
+            held -= cache.remove(0).archive.source.held_bytes();
+        }
+    }
+    Ok(archive)
+}
+
+fn join_member(base: &Path, member_path: &str) -> PathBuf {
+    member_path.split('/').fold(base.to_path_buf(), |p, c| p.join(c))
+}
+
+// Open the innermost archive on `path`. Returns it with its own path and the
+// path inside it. With `enter_last`, a final component that is an archive is
+// opened as well.
+fn resolve(path: &Path, enter_last: bool) -> Result<(Archive, PathBuf, String), ArchiveError> {
+    let file = path
+        .ancestors()
+        .find(|a| a.exists())
+        .filter(|a| a.is_file())
+        .ok_or_else(|| ArchiveError::from(format!("Path does not exist: {}", pathenc::encode(path))))?;
+    let rest: Vec<String> = path
+        .strip_prefix(file)
+        .unwrap_or(Path::new(""))
+        .iter()
+        .map(|c| c.to_string_lossy().to_string())
+        .collect();
+
+    let stamp = stamp(file);
+    let name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let mut archive = open_cached(file, stamp, || open_archive(Source::File(file.to_path_buf()), &name))?;
+    let mut key = file.to_path_buf();
+    let mut inner = String::new();
+    for (i, component) in rest.iter().enumerate() {
+        let candidate = if inner.is_empty() { component.clone() } else { format!("{}/{}", inner, component) };
+        let nested = archive
+            .members
+            .iter()
+            .rev()
+            .find(|m| m.path == candidate && !m.is_dir && !m.is_symlink)
+            .cloned();
+        match nested {
+            Some(member) if enter_last || i + 1 < rest.len() => {
+                key = join_member(&key, &candidate);
+                archive = open_cached(&key, stamp, || {
+                    let bytes = read_member(&archive, &member, NESTED_LIMIT + 1)?;
+                    if bytes.len() as u64 > NESTED_LIMIT {
+                        return Err(ArchiveError::from(format!("{} is too large to open inside an archive", component)));
+                    }
+                    open_archive(Source::Bytes(bytes.into()), component)
+                })?;
+                inner.clear();
+            }
+            _ => inner = candidate,
+        }
+    }
+    Ok((archive, key, inner))
+}
+
+fn file_entry(key: &Path, member: &Member, name: &str) -> FileEntry {
+    let extension = if member.is_dir {
+        String::new()
+    } else {
+        Path::new(name).extension().map(pathenc::encode).unwrap_or_default()
+    };
+    FileEntry {
+        name: name.to_string(),
+        display_name: None,
+        path: pathenc::encode(join_member(key, &member.path)),
+        is_dir: member.is_dir,
+        is_hidden: name.starts_with('.'),
+        size: if member.is_dir { 0 } else { member.size },
+        modified: member
+            .modified
+            .and_then(|secs| u64::try_from(secs).ok())
+            .map(commands::format_timestamp)
+            .unwrap_or_default(),
+        extension,
+        is_symlink: member.is_symlink,
+        modified_at: member.modified.map(|secs| Timestamp { secs, nanos: 0 }),
+        details: None,
+        in_archive: true,
+    }
+}
+
+// Direct children of `dir` ("" for the top level)
+fn children(archive: &Archive, key: &Path, dir: &str) -> Result<Vec<FileEntry>, ArchiveError> {
+    let prefix = if dir.is_empty() { String::new() } else { format!("{}/", dir) };
+    let mut found = dir.is_empty();
+    let mut children: BTreeMap<String, Member> = BTreeMap::new();
+    for member in archive.members.iter() {
+        if member.path == dir {
+            found = true;
+            continue;
+        }
+        let Some(rest) = member.path.strip_prefix(&prefix) else { continue };
+        found = true;
+        match rest.split_once('/') {
+            // Folders are not always stored, only the files in them
+            Some((name, _)) => {
+                children.entry(name.to_string()).or_insert_with(|| Member {
+                    path: format!("{}{}", prefix, name),
+                    index: member.index,
+                    is_dir: true,
+                    is_symlink: false,
+                    size: 0,
+                    modified: None,
+                });
+            }
+            // Later copies of a member replace earlier ones, as when extracting
+            None => {
+                children.insert(rest.to_string(), member.clone());
+            }
+        }
+    }
+    if !found {
+        return Err(not_found(dir));
+    }
+    Ok(children.iter().map(|(name, member)| file_entry(key, member, name)).collect())
+}
+
+/// List a folder inside an archive, or its top level. The path runs through
+/// the archive file as if it were a folder, and on through nested archives.
+/// Plain folders are listed as by list_directory.
+#[tauri::command]
+pub async fn list_archive(path: String, show_hidden: bool, options: Option<ListOptions>) -> Result<DirContents, ArchiveError> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<DirContents, ArchiveError> {
+        let virtual_path = pathenc::decode(&path);
+        if virtual_path.is_dir() {
+            return Ok(commands::list_directory(path, show_hidden, options)?);
+        }
+        let (archive, key, dir) = resolve(&virtual_path, true)?;
+        let mut entries = children(&archive, &key, &dir)?;
+        if !show_hidden {
+            entries.retain(|e| !e.is_hidden);
+        }
+        let groups = listing::apply(&mut entries, &options.unwrap_or_default())?;
+
+        Ok(DirContents {
+            path: pathenc::encode(&virtual_path),
+            entries,
+            parent: virtual_path.parent().map(pathenc::encode),
+            groups,
+            in_archive: true,
+        })
+    })
+    .await
+    .map_err(|e| ArchiveError::from(e.to_string()))?
+}
+
+/// Read a file inside an archive for previewing, up to `max_bytes` (50 MB by default)
+#[tauri::command]
+pub async fn read_archive_entry(path: String, max_bytes: Option<usize>) -> Result<tauri::ipc::Response, ArchiveError> {
+    tauri::async_runtime::spawn_blocking(move || -> Result<tauri::ipc::Response, ArchiveError> {
+        let (archive, _, inner) = resolve(&pathenc::decode(&path), false)?;
+        let member = archive
+            .members
+            .iter()
+            .rev()
+            .find(|m| m.path == inner && !m.is_dir)
+            .ok_or_else(|| not_found(&inner))?;
+        let limit = max_bytes.unwrap_or(50 * 1024 * 1024); // default 50 MB
+        Ok(tauri::ipc::Response::new(read_member(&archive, member, limit as u64)?))
+    })
+    .await
+    .map_err(|e| ArchiveError::from(e.to_string()))?
+}
@@ -76,12 +76,23 @@ mod platform {
     use std::process::{Command, Stdio};
     use std::io::Write;
 
+    // file:// URI with every byte outside the unreserved set percent-encoded
+    fn file_uri(path: &str) -> String {
+        use std::os::unix::ffi::OsStrExt;
+        let mut uri = String::from("file://");
+        for &b in crate::pathenc::decode(path).as_os_str().as_bytes() {
+            if b.is_ascii_alphanumeric() || b"/-._~".contains(&b) {
+                uri.push(b as char);
+            } else {
+                uri.push_str(&format!("%{:02X}", b));
+            }
+        }
+        uri
+    }
+
     pub fn write_files(paths: &[String], cut: bool) -> Result<(), String> {
         let operation = if cut { "cut" } else { "copy" };
-        let uris: Vec<String> = paths
-            .iter()
-            .map(|p| format!("file://{}", p))
-            .collect();
+        let uris: Vec<String> = paths.iter().map(|p| file_uri(p)).collect();
         let content = format!("{}\n{}", operation, uris.join("\n"));
 
         let mut child = Command::new("xclip")
@@ -133,9 +144,10 @@ mod platform {
         let paths: Vec<String> = lines
             .filter(|l| l.starts_with("file://"))
             .map(|l| {
-                urlencoding::decode(l.strip_prefix("file://").unwrap_or(l))
-                    .unwrap_or_else(|_| l.strip_prefix("file://").unwrap_or(l).into())
-                    .to_string()
+                use std::os::unix::ffi::OsStringExt;
+                let raw = l.strip_prefix("file://").unwrap_or(l);
+                let bytes = urlencoding::decode_binary(raw.as_bytes()).into_owned();
+                crate::pathenc::encode(std::ffi::OsString::from_vec(bytes))
             })
             .collect();
 
@@ -1,7 +1,17 @@
+use crate::acl::{self, Acl};
+use crate::dircache;
+use crate::dirsize::{self, SizeOptions};
+use crate::jobs;
+use crate::listing::{self, EntryGroup, ListOptions};
+use crate::metadata::{self, EntryDetails, NameCache, Timestamp};
+use crate::pathenc;
+use crate::xattrs::{self, Xattr};
 use serde::{Deserialize, Serialize};
 use std::fs;
+use std::collections::HashSet;
 use std::io::Read;
 use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
 use std::time::UNIX_EPOCH;
 use base64::Engine;
 use std::process::Command;
@@ -10,6 +20,9 @@ use tauri::async_runtime;
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct FileEntry {
     pub name: String,
+    /// Lossy, human-readable name when `name` had to escape non-UTF-8 bytes
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub display_name: Option<String>,
     pub path: String,
     pub is_dir: bool,
     pub is_hidden: bool,
@@ -17,6 +30,13 @@ pub struct FileEntry {
     pub modified: String,
     pub extension: String,
     pub is_symlink: bool,
+    #[serde(default)]
+    pub modified_at: Option<Timestamp>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub details: Option<EntryDetails>,
+    /// Member of an archive, listed by list_archive
+    #[serde(default)]
+    pub in_archive: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -24,13 +44,17 @@ pub struct DirContents {
     pub path: String,
     pub entries: Vec<FileEntry>,
     pub parent: Option<String>,
+    pub groups: Option<Vec<EntryGroup>>,
+    /// Listed from inside an archive, so there is nothing to watch
+    #[serde(default)]
+    pub in_archive: bool,
 }
 
 fn is_hidden(name: &str, _path: &Path) -> bool {
     name.starts_with('.')
 }
 
-fn format_timestamp(secs: u64) -> String {
+pub(crate) fn format_timestamp(secs: u64) -> String {
     let dt = chrono::DateTime::from_timestamp(secs as i64, 0);
     match dt {
         Some(d) => d.format("%Y-%m-%d %H:%M:%S").to_string(),
@@ -39,11 +63,14 @@ fn format_timestamp(secs: u64) -> String {
 }
 
 fn build_file_entry(path: &Path) -> Result<FileEntry, String> {
+    build_file_entry_with(path, None)
+}
+
+/// Build an entry, adding `details` when a name cache is supplied
+pub(crate) fn build_file_entry_with(path: &Path, names: Option<&mut NameCache>) -> Result<FileEntry, String> {
     let metadata = fs::symlink_metadata(path).map_err(|e| e.to_string())?;
-    let name = path
-        .file_name()
-        .map(|n| n.to_string_lossy().to_string())
-        .unwrap_or_default();
+    let name = path.file_name().map(pathenc::encode).unwrap_or_default();
+    let display_name = path.file_name().and_then(pathenc::display);
     let is_symlink = metadata.is_symlink();
     let real_metadata = if is_symlink {
         fs::metadata(path).unwrap_or(metadata.clone())
@@ -61,26 +88,35 @@ fn build_file_entry(path: &Path) -> Result<FileEntry, String> {
     let extension = if is_dir {
         String::new()
     } else {
-        path.extension()
-            .map(|e| e.to_string_lossy().to_string())
-            .unwrap_or_default()
+        path.extension().map(pathenc::encode).unwrap_or_default()
     };
 
+    let modified_at = metadata::modified_timestamp(&metadata);
+    let details = names.map(|names| metadata::entry_details(path, &metadata, names));
+
     Ok(FileEntry {
         name: name.clone(),
-        path: path.to_string_lossy().to_string(),
+        display_name,
+        path: pathenc::encode(path),
         is_dir,
         is_hidden: is_hidden(&name, path),
         size,
         modified,
         extension,
         is_symlink,
+        modified_at,
+        details,
+        in_archive: false,
     })
 }
 
 #[tauri::command]
-pub fn list_directory(path: String, show_hidden: bool) -> Result<DirContents, String> {
-    let dir_path = PathBuf::from(&path);
+pub fn list_directory(
+    path: String,
+    show_hidden: bool,
+    options: Option<ListOptions>,
+) -> Result<DirContents, String> {
+    let dir_path = pathenc::decode(&path);
     if !dir_path.exists() {
         return Err(format!("Path does not exist: {}", path));
     }
@@ -88,39 +124,46 @@ pub fn list_directory(path: String, show_hidden: bool) -> Result<DirContents, St
         return Err(format!("Path is not a directory: {}", path));
     }
 
-    let read_dir = fs::read_dir(&dir_path).map_err(|e| e.to_string())?;
-    let mut entries: Vec<FileEntry> = Vec::new();
-
-    for entry in read_dir {
-        if let Ok(entry) = entry {
-            if let Ok(file_entry) = build_file_entry(&entry.path()) {
-                if show_hidden || !file_entry.is_hidden {
+    let options = options.unwrap_or_default();
+    let mut entries = match dircache::get(&dir_path, options.detailed) {
+        Some(entries) => entries,
+        None => {
+            let stamp = dircache::stamp(&dir_path);
+            let read_dir = fs::read_dir(&dir_path).map_err(|e| e.to_string())?;
+            let mut names = NameCache::default();
+            let mut entries: Vec<FileEntry> = Vec::new();
+            for entry in read_dir.flatten() {
+                let names = if options.detailed { Some(&mut names) } else { None };
+                if let Ok(file_entry) = build_file_entry_with(&entry.path(), names) {
                     entries.push(file_entry);
                 }
             }
+            dircache::insert(&dir_path, stamp, options.detailed, entries.clone());
+            entries
         }
+    };
+    if !show_hidden {
+        entries.retain(|e| !e.is_hidden);
     }
 
-    // Sort: directories first, then alphabetical
-    entries.sort_by(|a, b| {
-        b.is_dir
-            .cmp(&a.is_dir)
-            .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
-    });
+    // Default: directories first, then case-insensitive by name
+    let groups = listing::apply(&mut entries, &options)?;
 
-    let parent = dir_path.parent().map(|p| p.to_string_lossy().to_string());
+    let parent = dir_path.parent().map(pathenc::encode);
 
     Ok(DirContents {
-        path: dir_path.to_string_lossy().to_string(),
+        path: pathenc::encode(&dir_path),
         entries,
         parent,
+        groups,
+        in_archive: false,
     })
 }
 
 #[tauri::command]
 pub fn get_home_directory() -> Result<String, String> {
     dirs::home_dir()
-        .map(|p| p.to_string_lossy().to_string())
+        .map(pathenc::encode)
         .ok_or_else(|| "Could not determine home directory".to_string())
 }
 
@@ -129,25 +172,25 @@ pub fn get_quick_access_paths() -> Result<Vec<(String, String)>, String> {
     let mut paths: Vec<(String, String)> = Vec::new();
 
     if let Some(home) = dirs::home_dir() {
-        paths.push(("Home".into(), home.to_string_lossy().to_string()));
+        paths.push(("Home".into(), pathenc::encode(&home)));
     }
     if let Some(desktop) = dirs::desktop_dir() {
-        paths.push(("Desktop".into(), desktop.to_string_lossy().to_string()));
+        paths.push(("Desktop".into(), pathenc::encode(&desktop)));
     }
     if let Some(docs) = dirs::document_dir() {
-        paths.push(("Documents".into(), docs.to_string_lossy().to_string()));
+        paths.push(("Documents".into(), pathenc::encode(&docs)));
     }
     if let Some(downloads) = dirs::download_dir() {
-        paths.push(("Downloads".into(), downloads.to_string_lossy().to_string()));
+        paths.push(("Downloads".into(), pathenc::encode(&downloads)));
     }
     if let Some(pictures) = dirs::picture_dir() {
-        paths.push(("Pictures".into(), pictures.to_string_lossy().to_string()));
+        paths.push(("Pictures".into(), pathenc::encode(&pictures)));
     }
     if let Some(music) = dirs::audio_dir() {
-        paths.push(("Music".into(), music.to_string_lossy().to_string()));
+        paths.push(("Music".into(), pathenc::encode(&music)));
     }
     if let Some(videos) = dirs::video_dir() {
-        paths.push(("Videos".into(), videos.to_string_lossy().to_string()));
+        paths.push(("Videos".into(), pathenc::encode(&videos)));
     }
 
     Ok(paths)
@@ -155,23 +198,23 @@ pub fn get_quick_access_paths() -> Result<Vec<(String, String)>, String> {
 
 #[tauri::command]
 pub fn create_directory(path: String, name: String) -> Result<String, String> {
-    let new_path = PathBuf::from(&path).join(&name);
+    let new_path = pathenc::decode(&path).join(pathenc::decode(&name));
     fs::create_dir_all(&new_path).map_err(|e| e.to_string())?;
-    Ok(new_path.to_string_lossy().to_string())
+    Ok(pathenc::encode(&new_path))
 }
 
 #[tauri::command]
 pub fn create_file(path: String, name: String) -> Result<String, String> {
-    let new_path = PathBuf::from(&path).join(&name);
+    let new_path = pathenc::decode(&path).join(pathenc::decode(&name));
     fs::File::create(&new_path).map_err(|e| e.to_string())?;
-    Ok(new_path.to_string_lossy().to_string())
+    Ok(pathenc::encode(&new_path))
 }
 
 #[tauri::command]
 pub async fn delete_items(paths: Vec<String>, use_trash: bool) -> Result<(), String> {
     async_runtime::spawn_blocking(move || {
         for path_str in &paths {
-            let path = PathBuf::from(path_str);
+            let path = pathenc::decode(path_str);
             if !path.exists() {
                 continue;
             }
@@ -191,17 +234,17 @@ pub async fn delete_items(paths: Vec<String>, use_trash: bool) -> Result<(), Str
 
 #[tauri::command]
 pub fn rename_item(old_path: String, new_name: String) -> Result<String, String> {
-    let old = PathBuf::from(&old_path);
+    let old = pathenc::decode(&old_path);
     let parent = old.parent().ok_or("No parent directory")?;
-    let new_path = parent.join(&new_name);
+    let new_path = parent.join(pathenc::decode(&new_name));
     fs::rename(&old, &new_path).map_err(|e| e.to_string())?;
-    Ok(new_path.to_string_lossy().to_string())
+    Ok(pathenc::encode(&new_path))
 }
 
 #[tauri::command]
 pub async fn copy_items(sources: Vec<String>, destination: String) -> Result<(), String> {
     async_runtime::spawn_blocking(move || {
-        let dest = PathBuf::from(&destination);
+        let dest = pathenc::decode(&destination);
         
         // Ensure destination exists and is a directory
         if !dest.exists() {
@@ -210,34 +253,33 @@ pub async fn copy_items(sources: Vec<String>, destination: String) -> Result<(),
         if !dest.is_dir() {
             return Err(format!("Destination is not a directory: {}", destination));
         }
+        // Keeps the volume from being unmounted underneath the transfer
+        let _writing = jobs::writing(&dest);
+        check_capacity(&dest, &sources)?;
 
-        for source_str in &sources {
-            let source = PathBuf::from(source_str);
+        for (done, source_str) in sources.iter().enumerate() {
+            let source = pathenc::decode(source_str);
             if !source.exists() {
                 return Err(format!("Source does not exist: {}", source_str));
             }
 
-            let file_name = source
-                .file_name()
-                .ok_or("Invalid file name")?
-                .to_string_lossy()
-                .to_string();
-            let target = dest.join(&file_name);
+            let file_name = source.file_name().ok_or("Invalid file name")?;
+            let target = dest.join(file_name);
             
             // Skip if source and target are the same
             if source.canonicalize().ok() == target.canonicalize().ok() {
                 continue;
             }
 
-            if source.is_dir() {
-                // Remove target if it exists
-                if target.exists() {
-                    fs::remove_dir_all(&target).map_err(|e| e.to_string())?;
-                }
-                copy_dir_recursive(&source, &target)?;
+            let copied = if source.is_dir() {
+                copy_dir_resumable(&source, &target)
             } else {
-                copy_with_retry(&source, &target)?;
-            }
+                copy_with_retry(&source, &target)
+            };
+            copied.map_err(|e| match sources.len() {
+                1 => String::from(e),
+                n => format!("{} ({} of {} items copied)", String::from(e), done, n),
+            })?;
         }
         Ok(())
     })
@@ -245,44 +287,142 @@ pub async fn copy_items(sources: Vec<String>, destination: String) -> Result<(),
     .map_err(|e| format!("Task failed: {}", e))?
 }
 
-fn copy_with_retry(src: &Path, dst: &Path) -> Result<(), String> {
+// Bytes the sources will take up once copied
+fn transfer_size(sources: &[String]) -> u64 {
+    sources
+        .iter()
+        .map(|s| {
+            let path = pathenc::decode(s);
+            match fs::metadata(&path) {
+                Ok(meta) if meta.is_dir() => dirsize::dir_size(&path, false).apparent,
+                Ok(meta) => meta.len(),
+                Err(_) => 0,
+            }
+        })
+        .sum()
+}
+
+fn check_capacity(dest: &Path, sources: &[String]) -> Result<(), String> {
+    #[cfg(unix)]
+    let space = statvfs_space(dest);
+    // get_drive_space looks Windows volumes up by drive letter
+    #[cfg(not(unix))]
+    let space = get_drive_space(dest.components().next().map(|c| pathenc::encode(c.as_os_str())).unwrap_or_default());
+    // Unknown capacity is not a reason to refuse the copy
+    let Ok(space) = space else {
+        return Ok(());
+    };
+    let needed = transfer_size(sources);
+    if space.total_bytes > 0 && needed > space.available_bytes {
+        return Err(format!(
+            "Not enough space on the destination: {} bytes needed, {} bytes available",
+            needed, space.available_bytes
+        ));
+    }
+    Ok(())
+}
+
+// Why a copy stopped; a full destination is told apart so the copy can be
+// paused and picked up again
+enum CopyError {
+    Full(String),
+    Failed(String),
+}
+
+impl From<std::io::Error> for CopyError {
+    fn from(e: std::io::Error) -> Self {
+        if e.kind() == std::io::ErrorKind::StorageFull {
+            CopyError::Full(e.to_string())
+        } else {
+            CopyError::Failed(e.to_string())
+        }
+    }
+}
+
+impl From<CopyError> for String {
+    fn from(e: CopyError) -> Self {
+        match e {
+            CopyError::Full(msg) => format!("{}. Free up some space and try again to resume", msg),
+            CopyError::Failed(msg) => msg,
+        }
+    }
+}
+
+fn copy_with_retry(src: &Path, dst: &Path) -> Result<(), CopyError> {
     const MAX_RETRIES: u32 = 3;
     const RETRY_DELAY_MS: u64 = 100;
 
     for attempt in 0..MAX_RETRIES {
         match fs::copy(src, dst) {
             Ok(_) => return Ok(()),
+            // Retrying cannot help; do not leave a truncated file behind
+            Err(e) if e.kind() == std::io::ErrorKind::StorageFull => {
+                let _ = fs::remove_file(dst);
+                return Err(CopyError::Full(format!("Destination is full, paused at {}", src.display())));
+            }
             Err(e) => {
                 if attempt < MAX_RETRIES - 1 {
                     std::thread::sleep(std::time::Duration::from_millis(RETRY_DELAY_MS));
                 } else {
-                    return Err(format!("Failed to copy after {} attempts: {}", MAX_RETRIES, e));
+                    return Err(CopyError::Failed(format!("Failed to copy after {} attempts: {}", MAX_RETRIES, e)));
                 }
             }
         }
     }
-    Err("Copy failed".to_string())
+    Err(CopyError::Failed("Copy failed".to_string()))
 }
 
-fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<(), String> {
-    fs::create_dir_all(dst).map_err(|e| e.to_string())?;
-    for entry in fs::read_dir(src).map_err(|e| e.to_string())? {
-        let entry = entry.map_err(|e| e.to_string())?;
+// Folder copies (source, target) that were paused when the destination
+// filled up. Their partial copies are kept, and copying the same folder there
+// again carries on from where it stopped instead of starting over.
+fn paused_copies() -> &'static Mutex<HashSet<(PathBuf, PathBuf)>> {
+    static PAUSED: OnceLock<Mutex<HashSet<(PathBuf, PathBuf)>>> = OnceLock::new();
+    PAUSED.get_or_init(|| Mutex::new(HashSet::new()))
+}
+
+// Copy a folder to `target`, replacing what is there, unless that is the partial
+// copy of a paused transfer of the same folder, which is then carried on
+fn copy_dir_resumable(source: &Path, target: &Path) -> Result<(), CopyError> {
+    let pair = (source.to_path_buf(), target.to_path_buf());
+    let resume = paused_copies().lock().map(|mut p| p.remove(&pair)).unwrap_or(false);
+    if target.exists() && !resume {
+        fs::remove_dir_all(target)?;
+    }
+    let copied = copy_dir_recursive(source, target, resume);
+    if let (Err(CopyError::Full(_)), Ok(mut paused)) = (&copied, paused_copies().lock()) {
+        paused.insert(pair);
+    }
+    copied
+}
+
+// With `resume`, files already copied in full are skipped; the one being
+// written when the destination filled up was removed again
+fn copy_dir_recursive(src: &Path, dst: &Path, resume: bool) -> Result<(), CopyError> {
+    fs::create_dir_all(dst)?;
+    for entry in fs::read_dir(src)? {
+        let entry = entry?;
         let src_path = entry.path();
         let dst_path = dst.join(entry.file_name());
         if src_path.is_dir() {
-            copy_dir_recursive(&src_path, &dst_path)?;
-        } else {
+            copy_dir_recursive(&src_path, &dst_path, resume)?;
+        } else if !(resume && same_length(&src_path, &dst_path)) {
             copy_with_retry(&src_path, &dst_path)?;
         }
     }
     Ok(())
 }
 
+fn same_length(a: &Path, b: &Path) -> bool {
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => b.is_file() && a.len() == b.len(),
+        _ => false,
+    }
+}
+
 #[tauri::command]
 pub async fn move_items(sources: Vec<String>, destination: String) -> Result<(), String> {
     async_runtime::spawn_blocking(move || {
-        let dest = PathBuf::from(&destination);
+        let dest = pathenc::decode(&destination);
         
         // Ensure destination exists and is a directory
         if !dest.exists() {
@@ -291,19 +431,26 @@ pub async fn move_items(sources: Vec<String>, destination: String) -> Result<(),
         if !dest.is_dir() {
             return Err(format!("Destination is not a directory: {}", destination));
         }
+        // Keeps the volume from being unmounted underneath the transfer
+        let _writing = jobs::writing(&dest);
+        // Only moves to another volume copy any data
+        let crossing: Vec<String> = sources
+            .iter()
+            .filter(|s| !same_volume(&pathenc::decode(s), &dest))
+            .cloned()
+            .collect();
+        if !crossing.is_empty() {
+            check_capacity(&dest, &crossing)?;
+        }
 
         for source_str in &sources {
-            let source = PathBuf::from(source_str);
+            let source = pathenc::decode(source_str);
             if !source.exists() {
                 return Err(format!("Source does not exist: {}", source_str));
             }
 
-            let file_name = source
-                .file_name()
-                .ok_or("Invalid file name")?
-                .to_string_lossy()
-                .to_string();
-            let target = dest.join(&file_name);
+            let file_name = source.file_name().ok_or("Invalid file name")?;
+            let target = dest.join(file_name);
             
             // Skip if source and target are the same
             if let (Ok(src_canon), Ok(dst_canon)) = (source.canonicalize(), target.canonicalize()) {
@@ -327,6 +474,7 @@ fn move_with_retry(src: &Path, dst: &Path) -> Result<(), String> {
     for attempt in 0..MAX_RETRIES {
         match fs::rename(src, dst) {
             Ok(_) => return Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::CrossesDevices => return move_across_volumes(src, dst),
             Err(e) => {
                 // If target exists, try to remove it first and retry
                 if dst.exists() && attempt == 0 {
@@ -353,9 +501,36 @@ fn move_with_retry(src: &Path, dst: &Path) -> Result<(), String> {
     Err("Move failed".to_string())
 }
 
+// Renaming cannot cross volumes, so the data is copied and the source removed
+// once the copy is complete
+fn move_across_volumes(src: &Path, dst: &Path) -> Result<(), String> {
+    if fs::symlink_metadata(src).map_err(|e| e.to_string())?.is_dir() {
+        copy_dir_resumable(src, dst)?;
+        fs::remove_dir_all(src).map_err(|e| e.to_string())
+    } else {
+        copy_with_retry(src, dst)?;
+        fs::remove_file(src).map_err(|e| e.to_string())
+    }
+}
+
+#[cfg(unix)]
+fn same_volume(a: &Path, b: &Path) -> bool {
+    use std::os::unix::fs::MetadataExt;
+    match (fs::metadata(a), fs::metadata(b)) {
+        (Ok(a), Ok(b)) => a.dev() == b.dev(),
+        _ => true,
+    }
+}
+
+// Windows volumes are told apart by their drive or share prefix
+#[cfg(not(unix))]
+fn same_volume(a: &Path, b: &Path) -> bool {
+    a.components().next() == b.components().next()
+}
+
 #[tauri::command]
 pub fn open_file(path: String) -> Result<(), String> {
-    open::that(&path).map_err(|e| e.to_string())
+    open::that(pathenc::decode(&path)).map_err(|e| e.to_string())
 }
 
 #[tauri::command]
@@ -363,7 +538,7 @@ pub async fn search_files(dir: String, query: String, show_hidden: bool) -> Resu
     async_runtime::spawn_blocking(move || {
         let query_lower = query.to_lowercase();
         let mut results: Vec<FileEntry> = Vec::new();
-        search_recursive(&PathBuf::from(&dir), &query_lower, show_hidden, &mut results, 0, 5)?;
+        search_recursive(&pathenc::decode(&dir), &query_lower, show_hidden, &mut results, 0, 5)?;
         Ok(results)
     })
     .await
@@ -408,13 +583,13 @@ fn search_recursive(
 
 #[tauri::command]
 pub fn get_file_details(path: String) -> Result<FileEntry, String> {
-    build_file_entry(&PathBuf::from(&path))
+    build_file_entry_with(&pathenc::decode(&path), Some(&mut NameCache::default()))
 }
 
 /// Read text file content (UTF-8, with size limit)
 #[tauri::command]
 pub fn read_file_text(path: String, max_bytes: Option<usize>) -> Result<String, String> {
-    let p = PathBuf::from(&path);
+    let p = pathenc::decode(&path);
     if !p.exists() {
         return Err(format!("File not found: {}", path));
     }
@@ -435,7 +610,7 @@ pub fn read_file_text(path: String, max_bytes: Option<usize>) -> Result<String,
 /// Read binary file as base64 (with size limit)
 #[tauri::command]
 pub fn read_file_base64(path: String, max_bytes: Option<usize>) -> Result<String, String> {
-    let p = PathBuf::from(&path);
+    let p = pathenc::decode(&path);
     if !p.exists() {
         return Err(format!("File not found: {}", path));
     }
@@ -452,46 +627,33 @@ pub fn read_file_base64(path: String, max_bytes: Option<usize>) -> Result<String
 }
 
 
-/// Calculate total size of a directory recursively (async, non-blocking)
+/// Calculate total size of a directory recursively (async, non-blocking).
+/// Apparent size by default; allocated blocks with `on_disk`.
 #[tauri::command]
-pub async fn calculate_dir_size(path: String) -> Result<u64, String> {
-    let dir_path = std::path::PathBuf::from(&path);
+pub async fn calculate_dir_size(path: String, options: Option<SizeOptions>) -> Result<u64, String> {
+    let options = options.unwrap_or_default();
+    let dir_path = pathenc::decode(&path);
     if !dir_path.is_dir() {
         return Err(format!("Not a directory: {}", path));
     }
     // Run blocking I/O on a separate thread
-    async_runtime::spawn_blocking(move || dir_size_recursive(&dir_path))
+    async_runtime::spawn_blocking(move || dirsize::dir_size(&dir_path, options.one_file_system).get(&options))
         .await
         .map_err(|e| format!("Task failed: {}", e))
 }
 
-fn dir_size_recursive(path: &std::path::Path) -> u64 {
-    let mut total: u64 = 0;
-    if let Ok(entries) = std::fs::read_dir(path) {
-        for entry in entries.flatten() {
-            let p = entry.path();
-            if p.is_dir() {
-                total += dir_size_recursive(&p);
-            } else if let Ok(meta) = p.metadata() {
-                total += meta.len();
-            }
-        }
-    }
-    total
-}
-
 /// Create a file with initial content (for templates)
 #[tauri::command]
 pub fn create_file_with_content(path: String, name: String, content: String) -> Result<String, String> {
-    let new_path = std::path::PathBuf::from(&path).join(&name);
+    let new_path = pathenc::decode(&path).join(pathenc::decode(&name));
     std::fs::write(&new_path, content.as_bytes()).map_err(|e| e.to_string())?;
-    Ok(new_path.to_string_lossy().to_string())
+    Ok(pathenc::encode(&new_path))
 }
 
 /// Show file/folder in the native file manager
 #[tauri::command]
 pub fn show_in_explorer(path: String) -> Result<(), String> {
-    let p = PathBuf::from(&path);
+    let p = pathenc::decode(&path);
     if !p.exists() {
         return Err(format!("Path does not exist: {}", path));
     }
@@ -507,14 +669,15 @@ pub fn show_in_explorer(path: String) -> Result<(), String> {
         // Try xdg-open on parent, or dbus for nautilus select
         let parent = p.parent().unwrap_or(&p);
         Command::new("xdg-open")
-            .arg(parent.to_string_lossy().to_string())
+            .arg(parent)
             .spawn()
             .map_err(|e| e.to_string())?;
     }
     #[cfg(target_os = "macos")]
     {
         Command::new("open")
-            .args(["-R", &path])
+            .arg("-R")
+            .arg(&p)
             .spawn()
             .map_err(|e| e.to_string())?;
     }
@@ -524,7 +687,7 @@ pub fn show_in_explorer(path: String) -> Result<(), String> {
 /// Open a terminal at the given directory
 #[tauri::command]
 pub fn open_in_terminal(path: String) -> Result<(), String> {
-    let dir = PathBuf::from(&path);
+    let dir = pathenc::decode(&path);
     let target_dir = if dir.is_dir() {
         dir
     } else {
@@ -546,12 +709,12 @@ pub fn open_in_terminal(path: String) -> Result<(), String> {
             let result = if *term == "gnome-terminal" {
                 Command::new(term)
                     .arg("--working-directory")
-                    .arg(target_dir.to_string_lossy().to_string())
+                    .arg(&target_dir)
                     .spawn()
             } else {
                 Command::new(term)
                     .arg("--workdir")
-                    .arg(target_dir.to_string_lossy().to_string())
+                    .arg(&target_dir)
                     .spawn()
             };
             if result.is_ok() {
@@ -566,7 +729,8 @@ pub fn open_in_terminal(path: String) -> Result<(), String> {
     #[cfg(target_os = "macos")]
     {
         Command::new("open")
-            .args(["-a", "Terminal", &target_dir.to_string_lossy()])
+            .args(["-a", "Terminal"])
+            .arg(&target_dir)
             .spawn()
             .map_err(|e| e.to_string())?;
     }
@@ -632,20 +796,42 @@ pub struct FileProperties {
     pub is_symlink: bool,
     pub extension: String,
     pub item_count: Option<u64>,
+    /// A folder's size is not known yet; `start_dir_size` computes it with progress
+    pub size_pending: bool,
+    /// Mode, ownership and exact timestamps of the item itself
+    pub details: EntryDetails,
+    pub xattrs: Vec<Xattr>,
+    /// POSIX ACLs; None where they are unsupported
+    pub acl: Option<Acl>,
 }
 
+/// Properties of a file or folder. Folder sizes are only filled in from the
+/// size cache so that opening Properties on a huge tree returns at once.
 #[tauri::command]
-pub fn get_file_properties(path: String) -> Result<FileProperties, String> {
-    let p = PathBuf::from(&path);
+pub async fn get_file_properties(path: String, one_file_system: Option<bool>) -> Result<FileProperties, String> {
+    async_runtime::spawn_blocking(move || file_properties(&path, one_file_system.unwrap_or(false)))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+fn file_properties(path: &str, one_file_system: bool) -> Result<FileProperties, String> {
+    let p = pathenc::decode(path);
     let meta = fs::symlink_metadata(&p).map_err(|e| e.to_string())?;
     let real_meta = if meta.is_symlink() {
         fs::metadata(&p).unwrap_or(meta.clone())
     } else {
         meta.clone()
     };
-    let name = p.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let name = p.file_name().map(pathenc::encode).unwrap_or_default();
     let is_dir = real_meta.is_dir();
-    let size = if is_dir { dir_size_recursive(&p) } else { real_meta.len() };
+    let (size, size_on_disk, size_pending) = if is_dir {
+        match dirsize::cached(&p, one_file_system) {
+            Some(total) => (total.apparent, total.on_disk, false),
+            None => (0, 0, true),
+        }
+    } else {
+        (real_meta.len(), dirsize::allocated_size(&real_meta), false)
+    };
     let modified = meta.modified().ok()
         .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
         .map(|d| format_timestamp(d.as_secs())).unwrap_or_default();
@@ -658,19 +844,20 @@ pub fn get_file_properties(path: String) -> Result<FileProperties, String> {
     let is_readonly = meta.permissions().readonly();
     let is_hidden_val = is_hidden(&name, &p);
     let extension = if is_dir { String::new() } else {
-        p.extension().map(|e| e.to_string_lossy().to_string()).unwrap_or_default()
+        p.extension().map(pathenc::encode).unwrap_or_default()
     };
     let item_count = if is_dir {
         fs::read_dir(&p).ok().map(|rd| rd.count() as u64)
     } else {
         None
     };
+    let mut names = NameCache::default();
     Ok(FileProperties {
         name,
-        path: p.to_string_lossy().to_string(),
+        path: pathenc::encode(&p),
         is_dir,
         size,
-        size_on_disk: size,
+        size_on_disk,
         modified,
         created,
         accessed,
@@ -679,6 +866,10 @@ pub fn get_file_properties(path: String) -> Result<FileProperties, String> {
         is_symlink: meta.is_symlink(),
         extension,
         item_count,
+        size_pending,
+        details: metadata::entry_details(&p, &meta, &mut names),
+        xattrs: xattrs::read_all(&p),
+        acl: acl::read_acl(&p, &mut names).ok(),
     })
 }
 
@@ -694,10 +885,68 @@ pub fn get_os_type() -> String {
     }
 }
 
+/// A drive or mount point; the mount details are only known on Linux
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct SystemDrive {
+    /// Display name
+    pub label: String,
+    pub path: String,
+    pub device: Option<String>,
+    pub fs_type: Option<String>,
+    pub options: Vec<String>,
+    pub read_only: bool,
+    pub noexec: bool,
+    /// Filesystem label, from /dev/disk/by-label
+    pub volume_label: Option<String>,
+    pub uuid: Option<String>,
+}
+
+impl SystemDrive {
+    #[cfg(not(target_os = "linux"))]
+    fn new(label: String, path: String) -> Self {
+        SystemDrive {
+            label,
+            path,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn linux_drives() -> Vec<SystemDrive> {
+    let mounts = crate::mounts::read_mounts();
+    let ids = crate::mounts::DiskIds::read();
+    crate::mounts::user_mounts(&mounts)
+        .into_iter()
+        .map(|m| {
+            let path = pathenc::encode(&m.mount_point);
+            let volume_label = ids.label(&m.source);
+            let label = match path.as_str() {
+                "/" => "Root (/)".to_string(),
+                "/tmp" => "Temp (/tmp)".to_string(),
+                _ => volume_label.clone().unwrap_or_else(|| {
+                    m.mount_point.file_name().map(pathenc::encode).unwrap_or_else(|| path.clone())
+                }),
+            };
+            SystemDrive {
+                label,
+                path,
+                device: (!m.source.is_empty() && m.source != "none").then(|| m.source.clone()),
+                fs_type: Some(m.fs_type.clone()),
+                read_only: m.read_only(),
+                noexec: m.options.iter().any(|o| o == "noexec"),
+                options: m.options.clone(),
+                uuid: ids.uuid(&m.source),
+                volume_label,
+            }
+        })
+        .collect()
+}
+
 /// List available drives / mount points
 #[tauri::command]
-pub fn get_system_drives() -> Result<Vec<(String, String)>, String> {
-    let mut drives: Vec<(String, String)> = Vec::new();
+pub fn get_system_drives() -> Result<Vec<SystemDrive>, String> {
+    let mut drives: Vec<SystemDrive> = Vec::new();
 
     #[cfg(target_os = "windows")]
     {
@@ -707,58 +956,26 @@ pub fn get_system_drives() -> Result<Vec<(String, String)>, String> {
             let path = PathBuf::from(&drive);
             if path.exists() {
                 let label = format!("Local Disk ({}:)", letter as char);
-                drives.push((label, drive));
+                drives.push(SystemDrive::new(label, drive));
             }
         }
     }
 
     #[cfg(target_os = "linux")]
     {
-        // Root filesystem
-        drives.push(("Root (/)".to_string(), "/".to_string()));
-
-        // /tmp
-        if PathBuf::from("/tmp").exists() {
-            drives.push(("Temp (/tmp)".to_string(), "/tmp".to_string()));
-        }
-
-        // Mounted volumes in /media and /mnt
-        for mount_dir in &["/media", "/mnt"] {
-            if let Ok(entries) = fs::read_dir(mount_dir) {
-                for entry in entries.flatten() {
-                    let p = entry.path();
-                    if p.is_dir() {
-                        let name = entry.file_name().to_string_lossy().to_string();
-                        // /media/<user>/<device> structure
-                        if *mount_dir == "/media" {
-                            if let Ok(sub_entries) = fs::read_dir(&p) {
-                                for sub in sub_entries.flatten() {
-                                    let sp = sub.path();
-                                    if sp.is_dir() {
-                                        let sname = sub.file_name().to_string_lossy().to_string();
-                                        drives.push((sname, sp.to_string_lossy().to_string()));
-                                    }
-                                }
-                            }
-                        } else {
-                            drives.push((name, p.to_string_lossy().to_string()));
-                        }
-                    }
-                }
-            }
-        }
+        drives.extend(linux_drives());
     }
 
     #[cfg(target_os = "macos")]
     {
-        drives.push(("Macintosh HD".to_string(), "/".to_string()));
+        drives.push(SystemDrive::new("Macintosh HD".to_string(), "/".to_string()));
         if let Ok(entries) = fs::read_dir("/Volumes") {
             for entry in entries.flatten() {
                 let p = entry.path();
                 if p.is_dir() {
-                    let name = entry.file_name().to_string_lossy().to_string();
+                    let name = pathenc::encode(entry.file_name());
                     if name != "Macintosh HD" {
-                        drives.push((name, p.to_string_lossy().to_string()));
+                        drives.push(SystemDrive::new(name, pathenc::encode(&p)));
                     }
                 }
             }
@@ -775,25 +992,25 @@ pub fn get_system_paths() -> Vec<(String, String, String)> {
 
     // Quick access paths from dirs crate
     if let Some(home) = dirs::home_dir() {
-        paths.push(("Home".into(), home.to_string_lossy().to_string(), "home".into()));
+        paths.push(("Home".into(), pathenc::encode(&home), "home".into()));
     }
     if let Some(desktop) = dirs::desktop_dir() {
-        paths.push(("Desktop".into(), desktop.to_string_lossy().to_string(), "desktop".into()));
+        paths.push(("Desktop".into(), pathenc::encode(&desktop), "desktop".into()));
     }
     if let Some(docs) = dirs::document_dir() {
-        paths.push(("Documents".into(), docs.to_string_lossy().to_string(), "documents".into()));
+        paths.push(("Documents".into(), pathenc::encode(&docs), "documents".into()));
     }
     if let Some(downloads) = dirs::download_dir() {
-        paths.push(("Downloads".into(), downloads.to_string_lossy().to_string(), "downloads".into()));
+        paths.push(("Downloads".into(), pathenc::encode(&downloads), "downloads".into()));
     }
     if let Some(pictures) = dirs::picture_dir() {
-        paths.push(("Pictures".into(), pictures.to_string_lossy().to_string(), "pictures".into()));
+        paths.push(("Pictures".into(), pathenc::encode(&pictures), "pictures".into()));
     }
     if let Some(music) = dirs::audio_dir() {
-        paths.push(("Music".into(), music.to_string_lossy().to_string(), "music".into()));
+        paths.push(("Music".into(), pathenc::encode(&music), "music".into()));
     }
     if let Some(videos) = dirs::video_dir() {
-        paths.push(("Videos".into(), videos.to_string_lossy().to_string(), "videos".into()));
+        paths.push(("Videos".into(), pathenc::encode(&videos), "videos".into()));
     }
 
     paths
@@ -803,14 +1020,14 @@ pub fn get_system_paths() -> Vec<(String, String, String)> {
 #[tauri::command]
 pub async fn duplicate_item(path: String) -> Result<String, String> {
     async_runtime::spawn_blocking(move || {
-        let source = PathBuf::from(&path);
+        let source = pathenc::decode(&path);
         if !source.exists() {
             return Err(format!("Source does not exist: {}", path));
         }
 
         let parent = source.parent().ok_or("Cannot get parent directory")?;
-        let stem = source.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
-        let ext = source.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+        let stem = source.file_stem().map(pathenc::encode).unwrap_or_default();
+        let ext = source.extension().map(|e| format!(".{}", pathenc::encode(e))).unwrap_or_default();
         
         // Find available copy name
         let mut counter = 1;
@@ -820,7 +1037,7 @@ pub async fn duplicate_item(path: String) -> Result<String, String> {
             } else {
                 format!("{} - Copy ({}){}", stem, counter, ext)
             };
-            let dest_path = parent.join(&copy_name);
+            let dest_path = parent.join(pathenc::decode(&copy_name));
             if !dest_path.exists() {
                 break dest_path;
             }
@@ -828,12 +1045,15 @@ pub async fn duplicate_item(path: String) -> Result<String, String> {
         };
 
         if source.is_dir() {
-            copy_dir_recursive(&source, &dest)?;
+            copy_dir_recursive(&source, &dest, false).map_err(|e| match e {
+                CopyError::Full(msg) => format!("{}; the partial copy is kept at {}", msg, dest.display()),
+                CopyError::Failed(msg) => msg,
+            })?;
         } else {
             fs::copy(&source, &dest).map_err(|e| e.to_string())?;
         }
 
-        Ok(dest.to_string_lossy().to_string())
+        Ok(pathenc::encode(&dest))
     })
     .await
     .map_err(|e| e.to_string())?
@@ -843,7 +1063,7 @@ pub async fn duplicate_item(path: String) -> Result<String, String> {
 #[tauri::command]
 pub async fn create_shortcut(source: String, link_path: String) -> Result<(), String> {
     async_runtime::spawn_blocking(move || {
-        let _src = PathBuf::from(&source);
+        let _src = pathenc::decode(&source);
         
         #[cfg(windows)]
         {
@@ -875,7 +1095,7 @@ pub async fn create_shortcut(source: String, link_path: String) -> Result<(), St
         
         #[cfg(unix)]
         {
-            std::os::unix::fs::symlink(&src, &link_path).map_err(|e| e.to_string())
+            std::os::unix::fs::symlink(&src, pathenc::decode(&link_path)).map_err(|e| e.to_string())
         }
     })
     .await
@@ -894,9 +1114,9 @@ pub async fn batch_rename(
         let mut results: Vec<(String, String)> = Vec::new();
         
         for path_str in paths {
-            let path = PathBuf::from(&path_str);
+            let path = pathenc::decode(&path_str);
             if let Some(filename) = path.file_name() {
-                let name = filename.to_string_lossy().to_string();
+                let name = pathenc::encode(filename);
                 let new_name = if use_regex {
                     match regex::Regex::new(&pattern) {
                         Ok(re) => re.replace_all(&name, replace_with.as_str()).to_string(),
@@ -908,10 +1128,10 @@ pub async fn batch_rename(
                 
                 if new_name != name {
                     if let Some(parent) = path.parent() {
-                        let new_path = parent.join(&new_name);
+                        let new_path = parent.join(pathenc::decode(&new_name));
                         if !new_path.exists() {
                             fs::rename(&path, &new_path).map_err(|e| e.to_string())?;
-                            results.push((path_str, new_path.to_string_lossy().to_string()));
+                            results.push((path_str, pathenc::encode(&new_path)));
                         }
                     }
                 }
@@ -930,8 +1150,45 @@ pub struct DriveSpaceInfo {
     pub path: String,
     pub total_bytes: u64,
     pub free_bytes: u64,
+    /// Free space usable without root; less than `free_bytes` when blocks are reserved
+    pub available_bytes: u64,
     pub used_bytes: u64,
     pub percent_used: f64,
+    /// 0 where the filesystem has no fixed inode count
+    pub total_inodes: u64,
+    pub free_inodes: u64,
+}
+
+/// Capacity of the filesystem holding `path`, which can be any path on it
+#[cfg(unix)]
+pub fn statvfs_space(path: &Path) -> Result<DriveSpaceInfo, String> {
+    use std::os::unix::ffi::OsStrExt;
+    let cpath = std::ffi::CString::new(path.as_os_str().as_bytes()).map_err(|e| e.to_string())?;
+    let mut st: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(cpath.as_ptr(), &mut st) } != 0 {
+        return Err(std::io::Error::last_os_error().to_string());
+    }
+
+    // Block counts are in fragment-size units; some filesystems leave that 0
+    let unit = if st.f_frsize > 0 { st.f_frsize } else { st.f_bsize } as u64;
+    let total = st.f_blocks as u64 * unit;
+    let free = st.f_bfree as u64 * unit;
+    let available = st.f_bavail as u64 * unit;
+    let used = total.saturating_sub(free);
+    // Like df: reserved blocks count as neither used nor usable
+    let usable = used + available;
+    let percent_used = if usable > 0 { (used as f64 / usable as f64) * 100.0 } else { 0.0 };
+
+    Ok(DriveSpaceInfo {
+        path: pathenc::encode(path.as_os_str()),
+        total_bytes: total,
+        free_bytes: free,
+        available_bytes: available,
+        used_bytes: used,
+        percent_used,
+        total_inodes: st.f_files as u64,
+        free_inodes: st.f_ffree as u64,
+    })
 }
 
 #[tauri::command]
@@ -975,15 +1232,23 @@ pub fn get_drive_space(path: String) -> Result<DriveSpaceInfo, String> {
             path,
             total_bytes: total,
             free_bytes: free,
+            available_bytes: free,
             used_bytes: used,
             percent_used,
+            total_inodes: 0,
+            free_inodes: 0,
         })
     }
-    
-    #[cfg(not(windows))]
+
+    #[cfg(unix)]
+    {
+        statvfs_space(&pathenc::decode(&path))
+    }
+
+    #[cfg(not(any(windows, unix)))]
     {
-        // For Unix, use statvfs
-        Err("Not implemented for this platform".to_string())
+        let _ = path;
+        Err("Not supported on this platform".to_string())
     }
 }
 
@@ -1085,7 +1350,7 @@ pub fn open_with_app(file_path: String, app_path: String) -> Result<(), String>
     #[cfg(not(windows))]
     {
         Command::new(&app_path)
-            .arg(&file_path)
+            .arg(pathenc::decode(&file_path))
             .spawn()
             .map_err(|e| e.to_string())?;
         Ok(())
@@ -0,0 +1,229 @@
+use crate::commands::{build_file_entry_with, FileEntry};
+use crate::dirsize;
+use crate::metadata::NameCache;
+use crate::mounts;
+use crate::pathenc;
+use crate::watcher::DEFAULT_POLL_INTERVAL_MS;
+use notify::{Config, Event, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, SystemTime};
+
+// Recently listed directories kept in memory; the least recently used is evicted
+const MAX_CACHED_DIRS: usize = 32;
+
+struct CachedDir {
+    entries: Vec<FileEntry>,
+    detailed: bool,
+    last_used: u64,
+}
+
+#[derive(Default)]
+struct DirCache {
+    dirs: HashMap<PathBuf, CachedDir>,
+    tick: u64,
+}
+
+// The watcher lives behind its own lock: notify's event thread calls `on_event`,
+// which takes the cache lock, so the cache lock must never be held across
+// `watch`/`unwatch` (they wait on that same thread).
+fn cache() -> &'static Mutex<DirCache> {
+    static CACHE: OnceLock<Mutex<DirCache>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(DirCache::default()))
+}
+
+// Remote changes never reach inotify, so directories on network mounts are
+// polled instead
+struct Watchers {
+    native: Option<RecommendedWatcher>,
+    poll: Option<PollWatcher>,
+    polled: HashSet<PathBuf>,
+}
+
+impl Watchers {
+    fn watch(&mut self, dir: &Path) -> bool {
+        let polled = mounts::is_network_path(dir);
+        let watcher: Option<&mut dyn Watcher> = if polled {
+            self.poll.as_mut().map(|w| w as _)
+        } else {
+            self.native.as_mut().map(|w| w as _)
+        };
+        let Some(watcher) = watcher else { return false };
+        if watcher.watch(dir, RecursiveMode::NonRecursive).is_err() {
+            return false;
+        }
+        if polled {
+            self.polled.insert(dir.to_path_buf());
+        }
+        true
+    }
+
+    fn unwatch(&mut self, dir: &Path) {
+        let watcher: Option<&mut dyn Watcher> = if self.polled.remove(dir) {
+            self.poll.as_mut().map(|w| w as _)
+        } else {
+            self.native.as_mut().map(|w| w as _)
+        };
+        if let Some(watcher) = watcher {
+            let _ = watcher.unwatch(dir);
+        }
+    }
+}
+
+fn handle(res: Result<Event, notify::Error>) {
+    match res {
+        Ok(event) => on_event(&event),
+        Err(e) => eprintln!("Directory cache watch error: {}", e),
+    }
+}
+
+fn watcher() -> &'static Mutex<Watchers> {
+    static WATCHER: OnceLock<Mutex<Watchers>> = OnceLock::new();
+    WATCHER.get_or_init(|| {
+        let native = RecommendedWatcher::new(handle, Config::default())
+            .map_err(|e| eprintln!("Failed to create directory cache watcher: {}", e))
+            .ok();
+        let interval = Duration::from_millis(DEFAULT_POLL_INTERVAL_MS);
+        let poll = PollWatcher::new(handle, Config::default().with_poll_interval(interval))
+            .map_err(|e| eprintln!("Failed to create directory cache poller: {}", e))
+            .ok();
+        Mutex::new(Watchers {
+            native,
+            poll,
+            polled: HashSet::new(),
+        })
+    })
+}
+
+/// Cached entries for `dir` (hidden ones included), if still valid.
+/// A cache built without details cannot serve a detailed request.
+pub fn get(dir: &Path, detailed: bool) -> Option<Vec<FileEntry>> {
+    let mut cache = cache().lock().ok()?;
+    cache.tick += 1;
+    let tick = cache.tick;
+    let cached = cache.dirs.get_mut(dir)?;
+    if detailed && !cached.detailed {
+        return None;
+    }
+    cached.last_used = tick;
+    Some(cached.entries.clone())
+}
+
+/// Directory mtime to take before listing and hand back to `insert`
+pub fn stamp(dir: &Path) -> Option<SystemTime> {
+    std::fs::metadata(dir).and_then(|m| m.modified()).ok()
+}
+
+/// Remember a fresh listing and start watching the directory so it stays valid.
+/// `stamp` is the directory's mtime from before the listing was read; if it
+/// moved since, something changed before the watch was in place and the
+/// listing is not cached.
+pub fn insert(dir: &Path, stamp: Option<SystemTime>, detailed: bool, entries: Vec<FileEntry>) {
+    // Held until the listing is cached, so a deferred unwatch from `on_event`
+    // cannot slip in between watching the directory and caching it
+    let Ok(mut w) = watcher().lock() else { return };
+    let already_watched = cache()
+        .lock()
+        .map(|c| c.dirs.contains_key(dir))
+        .unwrap_or(false);
+    // Without a watch we cannot tell when the listing goes stale
+    if !already_watched && !w.watch(dir) {
+        return;
+    }
+    if stamp.is_none() || self::stamp(dir) != stamp {
+        if !already_watched {
+            w.unwatch(dir);
+        }
+        return;
+    }
+
+    let Ok(mut cache) = cache().lock() else { return };
+    cache.tick += 1;
+    let tick = cache.tick;
+    cache.dirs.insert(
+        dir.to_path_buf(),
+        CachedDir {
+            entries,
+            detailed,
+            last_used: tick,
+        },
+    );
+    let mut evicted = Vec::new();
+    while cache.dirs.len() > MAX_CACHED_DIRS {
+        let oldest = cache
+            .dirs
+            .iter()
+            .min_by_key(|(_, c)| c.last_used)
+            .map(|(p, _)| p.clone());
+        match oldest {
+            Some(p) => {
+                cache.dirs.remove(&p);
+                evicted.push(p);
+            }
+            None => break,
+        }
+    }
+    drop(cache);
+    for dir in &evicted {
+        w.unwatch(dir);
+    }
+}
+
+// Unwatch directories dropped from the cache, unless they were cached again since
+fn unwatch_dropped(dirs: &[PathBuf]) {
+    let Ok(mut w) = watcher().lock() else { return };
+    let Ok(cache) = cache().lock() else { return };
+    let dirs: Vec<&PathBuf> = dirs.iter().filter(|d| !cache.dirs.contains_key(*d)).collect();
+    drop(cache);
+    for dir in dirs {
+        w.unwatch(dir);
+    }
+}
+
+// Patch cached listings from a watcher event: every path the event mentions is
+// re-stat'ed and upserted into (or removed from) its parent's cached entries.
+fn on_event(event: &Event) {
+    dirsize::invalidate(&event.paths);
+
+    let dropped = patch(event);
+    if !dropped.is_empty() {
+        // Unwatching waits on the thread delivering this event, so hand it off
+        std::thread::spawn(move || unwatch_dropped(&dropped));
+    }
+}
+
+// Returns the directories dropped from the cache, which still need unwatching
+fn patch(event: &Event) -> Vec<PathBuf> {
+    let Ok(mut cache) = cache().lock() else { return Vec::new() };
+
+    if event.need_rescan() {
+        return cache.dirs.drain().map(|(dir, _)| dir).collect();
+    }
+
+    let mut dropped = Vec::new();
+    let mut names = NameCache::default();
+    for path in &event.paths {
+        // The cached directory itself went away
+        if cache.dirs.contains_key(path) && !path.is_dir() {
+            cache.dirs.remove(path);
+            dropped.push(path.clone());
+            continue;
+        }
+        let Some(parent) = path.parent() else { continue };
+        let Some(cached) = cache.dirs.get_mut(parent) else { continue };
+
+        let path_str = pathenc::encode(path);
+        let position = cached.entries.iter().position(|e| e.path == path_str);
+        let names = if cached.detailed { Some(&mut names) } else { None };
+        match (build_file_entry_with(path, names), position) {
+            (Ok(entry), Some(i)) => cached.entries[i] = entry,
+            (Ok(entry), None) => cached.entries.push(entry),
+            (Err(_), Some(i)) => {
+                cached.entries.swap_remove(i);
+            }
+            (Err(_), None) => {}
+        }
+    }
+    dropped
+}
@@ -0,0 +1,295 @@
+use crate::jobs;
+use crate::pathenc;
+use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+// How often a running size job reports its partial totals
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+// Cached totals only see changes in watched directories, so they also expire
+const CACHE_MAX_AGE: Duration = Duration::from_secs(300);
+const MAX_CACHED_SIZES: usize = 256;
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct SizeOptions {
+    /// Report allocated blocks (`du`) instead of file lengths (`du --apparent-size`)
+    pub on_disk: bool,
+    /// Do not descend into directories on other filesystems (`du -x`)
+    pub one_file_system: bool,
+}
+
+/// Totals for a directory tree; hardlinked files are counted once
+#[derive(Debug, Serialize, Clone, Copy, Default)]
+pub struct DirSize {
+    /// Sum of file lengths
+    pub apparent: u64,
+    /// Allocated space, including the directories themselves
+    pub on_disk: u64,
+    pub files: u64,
+    pub dirs: u64,
+}
+
+impl DirSize {
+    pub fn get(&self, options: &SizeOptions) -> u64 {
+        if options.on_disk {
+            self.on_disk
+        } else {
+            self.apparent
+        }
+    }
+}
+
+/// Payload of the "dir-size-progress" event
+#[derive(Debug, Serialize, Clone)]
+pub struct DirSizeProgress {
+    pub id: u64,
+    pub path: String,
+    /// Running totals; final once `done` is set
+    pub size: DirSize,
+    pub done: bool,
+    pub cancelled: bool,
+}
+
+/// Space actually allocated for a file: less than its length when sparse,
+/// a whole block when tiny
+pub fn allocated_size(meta: &fs::Metadata) -> u64 {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        // st_blocks is always in 512-byte units, whatever the filesystem block size
+        meta.blocks() * 512
+    }
+    #[cfg(not(unix))]
+    {
+        meta.len()
+    }
+}
+
+#[cfg(unix)]
+fn device(meta: &fs::Metadata) -> u64 {
+    use std::os::unix::fs::MetadataExt;
+    meta.dev()
+}
+
+#[cfg(not(unix))]
+fn device(_meta: &fs::Metadata) -> u64 {
+    0
+}
+
+// (device, inode) of a file with more than one link, so each is counted once
+#[cfg(unix)]
+fn hardlink_id(meta: &fs::Metadata) -> Option<(u64, u64)> {
+    use std::os::unix::fs::MetadataExt;
+    (meta.nlink() > 1 && !meta.is_dir()).then(|| (meta.dev(), meta.ino()))
+}
+
+#[cfg(not(unix))]
+fn hardlink_id(_meta: &fs::Metadata) -> Option<(u64, u64)> {
+    None
+}
+
+// Shared by every thread of one walk; totals are readable while it runs
+#[derive(Default)]
+struct Walk {
+    apparent: AtomicU64,
+    on_disk: AtomicU64,
+    files: AtomicU64,
+    dirs: AtomicU64,
+    seen: Mutex<HashSet<(u64, u64)>>,
+    cancel: Arc<AtomicBool>,
+    root_dev: Option<u64>,
+}
+
+impl Walk {
+    fn totals(&self) -> DirSize {
+        DirSize {
+            apparent: self.apparent.load(Ordering::Relaxed),
+            on_disk: self.on_disk.load(Ordering::Relaxed),
+            files: self.files.load(Ordering::Relaxed),
+            dirs: self.dirs.load(Ordering::Relaxed),
+        }
+    }
+
+    // Symlinks are never followed (DirEntry::metadata does not traverse them),
+    // so link cycles cannot send the walk in circles
+    fn dir(&self, dir: &Path) {
+        if self.cancel.load(Ordering::Relaxed) {
+            return;
+        }
+        let Ok(entries) = fs::read_dir(dir) else { return };
+        let mut subdirs = Vec::new();
+        let (mut apparent, mut on_disk, mut files) = (0, 0, 0);
+        for entry in entries.flatten() {
+            let Ok(meta) = entry.metadata() else { continue };
+            if self.root_dev.is_some_and(|dev| device(&meta) != dev) {
+                continue;
+            }
+            if let Some(id) = hardlink_id(&meta) {
+                if !self.seen.lock().map(|mut s| s.insert(id)).unwrap_or(true) {
+                    continue;
+                }
+            }
+            on_disk += allocated_size(&meta);
+            if meta.is_dir() {
+                subdirs.push(entry.path());
+            } else {
+                files += 1;
+                apparent += meta.len();
+            }
+        }
+        self.apparent.fetch_add(apparent, Ordering::Relaxed);
+        self.on_disk.fetch_add(on_disk, Ordering::Relaxed);
+        self.files.fetch_add(files, Ordering::Relaxed);
+        self.dirs.fetch_add(subdirs.len() as u64, Ordering::Relaxed);
+        subdirs.par_iter().for_each(|d| self.dir(d));
+    }
+}
+
+fn new_walk(root: &Path, one_file_system: bool, cancel: Arc<AtomicBool>) -> Option<Walk> {
+    let root_meta = fs::symlink_metadata(root).ok()?;
+    let walk = Walk {
+        cancel,
+        root_dev: one_file_system.then(|| device(&root_meta)),
+        ..Walk::default()
+    };
+    walk.on_disk.store(allocated_size(&root_meta), Ordering::Relaxed);
+    Some(walk)
+}
+
+struct CachedSize {
+    size: DirSize,
+    computed: Instant,
+}
+
+type CacheKey = (PathBuf, bool);
+
+fn cache() -> &'static Mutex<HashMap<CacheKey, CachedSize>> {
+    static CACHE: OnceLock<Mutex<HashMap<CacheKey, CachedSize>>> = OnceLock::new();
+    CACHE.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+/// Previously computed totals for `root`, if nothing has invalidated them
+pub fn cached(root: &Path, one_file_system: bool) -> Option<DirSize> {
+    let mut cache = cache().lock().ok()?;
+    let key = (root.to_path_buf(), one_file_system);
+    match cache.get(&key) {
+        Some(c) if c.computed.elapsed() < CACHE_MAX_AGE => Some(c.size),
+        Some(_) => {
+            cache.remove(&key);
+            None
+        }
+        None => None,
+    }
+}
+
+fn store(root: &Path, one_file_system: bool, size: DirSize) {
+    let Ok(mut cache) = cache().lock() else { return };
+    cache.insert(
+        (root.to_path_buf(), one_file_system),
+        CachedSize {
+            size,
+            computed: Instant::now(),
+        },
+    );
+    while cache.len() > MAX_CACHED_SIZES {
+        let oldest = cache.iter().min_by_key(|(_, c)| c.computed).map(|(k, _)| k.clone());
+        match oldest {
+            Some(k) => cache.remove(&k),
+            None => break,
+        };
+    }
+}
+
+/// Forget every cached total that a change at one of `paths` affects:
+/// those of its ancestors, and those below it when a whole subtree moved
+pub fn invalidate(paths: &[PathBuf]) {
+    if paths.is_empty() {
+        return;
+    }
+    let Ok(mut cache) = cache().lock() else { return };
+    cache.retain(|(root, _), _| !paths.iter().any(|p| p.starts_with(root) || root.starts_with(p)));
+}
+
+/// Walk a directory tree in parallel, using and filling the cache
+pub fn dir_size(root: &Path, one_file_system: bool) -> DirSize {
+    if let Some(size) = cached(root, one_file_system) {
+        return size;
+    }
+    let Some(walk) = new_walk(root, one_file_system, Arc::default()) else {
+        return DirSize::default();
+    };
+    walk.dir(root);
+    let size = walk.totals();
+    store(root, one_file_system, size);
+    size
+}
+
+/// Start sizing `path` in the background and return a job ID. Progress arrives
+/// as "dir-size-progress" events; the last one has `done` set.
+#[tauri::command]
+pub fn start_dir_size(app: AppHandle, path: String, options: Option<SizeOptions>) -> Result<u64, String> {
+    let one_file_system = options.unwrap_or_default().one_file_system;
+    let root = pathenc::decode(&path);
+    if !root.is_dir() {
+        return Err(format!("Not a directory: {}", path));
+    }
+
+    let (id, cancel) = jobs::start()?;
+
+    std::thread::spawn(move || {
+        let emit = |size: DirSize, done: bool, cancelled: bool| {
+            let _ = app.emit(
+                "dir-size-progress",
+                DirSizeProgress {
+                    id,
+                    path: path.clone(),
+                    size,
+                    done,
+                    cancelled,
+                },
+            );
+        };
+
+        if let Some(size) = cached(&root, one_file_system) {
+            emit(size, true, false);
+        } else if let Some(walk) = new_walk(&root, one_file_system, cancel.clone()) {
+            let (done_tx, done_rx) = mpsc::channel::<()>();
+            std::thread::scope(|s| {
+                let (walk, root) = (&walk, &root);
+                s.spawn(move || {
+                    walk.dir(root);
+                    drop(done_tx);
+                });
+                while let Err(RecvTimeoutError::Timeout) = done_rx.recv_timeout(PROGRESS_INTERVAL) {
+                    emit(walk.totals(), false, false);
+                }
+            });
+            let cancelled = cancel.load(Ordering::Relaxed);
+            if !cancelled {
+                store(&root, one_file_system, walk.totals());
+            }
+            emit(walk.totals(), true, cancelled);
+        } else {
+            emit(DirSize::default(), true, false);
+        }
+
+        jobs::finish(id);
+    });
+
+    Ok(id)
+}
+
+/// Stop a running size job; its final event reports `cancelled`
+#[tauri::command]
+pub fn cancel_dir_size(id: u64) -> Result<(), String> {
+    jobs::cancel(id)
+}
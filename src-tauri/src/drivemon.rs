@@ -0,0 +1,138 @@
+// Background monitor for mounts and removable media. On Linux the thread
+// sleeps in poll(2) on /proc/self/mountinfo, which the kernel wakes on every
+// mount change; the timeout catches media that is plugged in but not mounted.
+use crate::commands::get_system_drives;
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+const RESCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Payload of the "drives-changed" event. Mount points are listed under
+/// `mounted`/`unmounted`, removable device nodes under `added`/`removed`.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct DrivesChangedEvent {
+    pub mounted: Vec<String>,
+    pub unmounted: Vec<String>,
+    pub added: Vec<String>,
+    pub removed: Vec<String>,
+}
+
+impl DrivesChangedEvent {
+    fn is_empty(&self) -> bool {
+        self.mounted.is_empty() && self.unmounted.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+#[derive(Default, PartialEq)]
+struct Snapshot {
+    /// Mount point -> device, so remounting another device over it counts as a change
+    mounts: BTreeMap<String, Option<String>>,
+    /// Removable device node -> display name
+    removable: BTreeMap<String, String>,
+}
+
+fn snapshot() -> Snapshot {
+    let mounts = get_system_drives()
+        .unwrap_or_default()
+        .into_iter()
+        .map(|d| (d.path, d.device))
+        .collect();
+    #[cfg(target_os = "linux")]
+    let removable = crate::removable::removable_volumes(
+        std::path::Path::new("/sys"),
+        std::path::Path::new("/dev"),
+        &crate::mounts::read_mounts(),
+    )
+    .into_iter()
+    .filter_map(|d| Some((d.device?, d.name)))
+    .collect();
+    #[cfg(not(target_os = "linux"))]
+    let removable = BTreeMap::new();
+    Snapshot { mounts, removable }
+}
+
+fn diff(old: &Snapshot, new: &Snapshot) -> DrivesChangedEvent {
+    let changed = |a: &BTreeMap<String, Option<String>>, b: &BTreeMap<String, Option<String>>| {
+        a.iter().filter(|(k, v)| b.get(*k) != Some(v)).map(|(k, _)| k.clone()).collect()
+    };
+    DrivesChangedEvent {
+        mounted: changed(&new.mounts, &old.mounts),
+        unmounted: changed(&old.mounts, &new.mounts),
+        added: new.removable.keys().filter(|k| !old.removable.contains_key(*k)).cloned().collect(),
+        removed: old.removable.keys().filter(|k| !new.removable.contains_key(*k)).cloned().collect(),
+    }
+}
+
+// Block until the mount table may have changed or the interval has passed
+#[cfg(target_os = "linux")]
+fn wait_for_change(mountinfo: Option<&std::fs::File>) {
+    use std::os::unix::io::AsRawFd;
+    match mountinfo {
+        Some(file) => {
+            let mut pfd = libc::pollfd {
+                fd: file.as_raw_fd(),
+                events: libc::POLLPRI,
+                revents: 0,
+            };
+            unsafe { libc::poll(&mut pfd, 1, RESCAN_INTERVAL.as_millis() as libc::c_int) };
+        }
+        None => std::thread::sleep(RESCAN_INTERVAL),
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn wait_for_change(_mountinfo: Option<&std::fs::File>) {
+    std::thread::sleep(RESCAN_INTERVAL);
+}
+
+// Tell the user about new media while the window is hidden in the tray
+fn notify_added(app: &AppHandle, names: &[String]) {
+    use tauri_plugin_notification::NotificationExt;
+    let hidden = app
+        .get_webview_window("main")
+        .map(|w| !w.is_visible().unwrap_or(true))
+        .unwrap_or(true);
+    if !hidden {
+        return;
+    }
+    for name in names {
+        let _ = app
+            .notification()
+            .builder()
+            .title("Drive connected")
+            .body(name)
+            .show();
+    }
+}
+
+/// Start the monitor thread; called once from setup
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || {
+        // Each poll(2) that reports a change also re-arms it for the next one
+        #[cfg(target_os = "linux")]
+        let mountinfo = std::fs::File::open("/proc/self/mountinfo").ok();
+        #[cfg(not(target_os = "linux"))]
+        let mountinfo: Option<std::fs::File> = None;
+
+        let mut last = snapshot();
+        loop {
+            wait_for_change(mountinfo.as_ref());
+
+            let current = snapshot();
+            if current == last {
+                continue;
+            }
+            let event = diff(&last, &current);
+            if !event.added.is_empty() {
+                let names: Vec<String> = event.added.iter().filter_map(|d| current.removable.get(d).cloned()).collect();
+                notify_added(&app, &names);
+            }
+            if !event.is_empty() {
+                let _ = app.emit("drives-changed", event);
+            }
+            last = current;
+        }
+    });
+}
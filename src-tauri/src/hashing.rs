@@ -0,0 +1,198 @@
+use crate::jobs;
+use crate::pathenc;
+use serde::{Deserialize, Serialize};
+use sha2::Digest;
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+
+const READ_CHUNK: usize = 1024 * 1024;
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum HashAlgorithm {
+    Md5,
+    Sha1,
+    Sha256,
+    Sha512,
+    Blake3,
+    Crc32,
+}
+
+enum Hasher {
+    Md5(md5::Md5),
+    Sha1(sha1::Sha1),
+    Sha256(sha2::Sha256),
+    Sha512(sha2::Sha512),
+    Blake3(Box<blake3::Hasher>),
+    Crc32(crc32fast::Hasher),
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+impl Hasher {
+    fn new(algorithm: HashAlgorithm) -> Self {
+        match algorithm {
+            HashAlgorithm::Md5 => Hasher::Md5(md5::Md5::new()),
+            HashAlgorithm::Sha1 => Hasher::Sha1(sha1::Sha1::new()),
+            HashAlgorithm::Sha256 => Hasher::Sha256(sha2::Sha256::new()),
+            HashAlgorithm::Sha512 => Hasher::Sha512(sha2::Sha512::new()),
+            HashAlgorithm::Blake3 => Hasher::Blake3(Box::default()),
+            HashAlgorithm::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+        }
+    }
+
+    fn update(&mut self, data: &[u8]) {
+        match self {
+            Hasher::Md5(h) => h.update(data),
+            Hasher::Sha1(h) => h.update(data),
+            Hasher::Sha256(h) => h.update(data),
+            Hasher::Sha512(h) => h.update(data),
+            Hasher::Blake3(h) => {
+                h.update(data);
+            }
+            Hasher::Crc32(h) => h.update(data),
+        }
+    }
+
+    /// Lowercase hex digest, as printed by `sha256sum` and friends
+    fn finish(self) -> String {
+        match self {
+            Hasher::Md5(h) => hex(&h.finalize()),
+            Hasher::Sha1(h) => hex(&h.finalize()),
+            Hasher::Sha256(h) => hex(&h.finalize()),
+            Hasher::Sha512(h) => hex(&h.finalize()),
+            Hasher::Blake3(h) => h.finalize().to_hex().to_string(),
+            Hasher::Crc32(h) => format!("{:08x}", h.finalize()),
+        }
+    }
+}
+
+/// Stream a file through every requested algorithm at once. `on_progress`
+/// gets the number of bytes read so far; a set `cancel` flag aborts.
+pub fn hash_file(
+    path: &Path,
+    algorithms: &[HashAlgorithm],
+    cancel: &AtomicBool,
+    mut on_progress: impl FnMut(u64),
+) -> Result<BTreeMap<HashAlgorithm, String>, String> {
+    let mut file = fs::File::open(path).map_err(|e| e.to_string())?;
+    let mut hashers: Vec<(HashAlgorithm, Hasher)> = algorithms.iter().map(|&a| (a, Hasher::new(a))).collect();
+    let mut buf = vec![0u8; READ_CHUNK];
+    let mut done: u64 = 0;
+    loop {
+        if cancel.load(Ordering::Relaxed) {
+            return Err("Cancelled".to_string());
+        }
+        let n = match file.read(&mut buf) {
+            Ok(0) => break,
+            Ok(n) => n,
+            Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e.to_string()),
+        };
+        for (_, h) in hashers.iter_mut() {
+            h.update(&buf[..n]);
+        }
+        done += n as u64;
+        on_progress(done);
+    }
+    Ok(hashers.into_iter().map(|(a, h)| (a, h.finish())).collect())
+}
+
+/// Digests of one file, or why it could not be read
+#[derive(Debug, Serialize, Clone)]
+pub struct FileHashes {
+    pub path: String,
+    pub size: u64,
+    pub hashes: BTreeMap<HashAlgorithm, String>,
+    pub error: Option<String>,
+}
+
+/// Payload of the "hash-progress" event
+#[derive(Debug, Serialize, Clone)]
+pub struct HashProgress {
+    pub id: u64,
+    /// File currently being hashed
+    pub path: String,
+    pub file_index: usize,
+    pub file_count: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+    /// Set once the file at `file_index` is finished
+    pub result: Option<FileHashes>,
+    pub done: bool,
+    pub cancelled: bool,
+}
+
+/// Hash files in the background and return a job ID. Progress and each
+/// file's digests arrive as "hash-progress" events; the last one has `done` set.
+#[tauri::command]
+pub fn compute_hashes(app: AppHandle, paths: Vec<String>, algorithms: Vec<HashAlgorithm>) -> Result<u64, String> {
+    if algorithms.is_empty() {
+        return Err("No hash algorithm given".to_string());
+    }
+    let (id, cancel) = jobs::start()?;
+
+    std::thread::spawn(move || {
+        let sizes: Vec<u64> = paths
+            .iter()
+            .map(|p| fs::metadata(pathenc::decode(p)).map(|m| m.len()).unwrap_or(0))
+            .collect();
+        let bytes_total: u64 = sizes.iter().sum();
+        let mut bytes_before: u64 = 0;
+        let progress = |index: usize, bytes_done: u64, result: Option<FileHashes>, done: bool| HashProgress {
+            id,
+            path: paths.get(index).cloned().unwrap_or_default(),
+            file_index: index,
+            file_count: paths.len(),
+            bytes_done,
+            bytes_total,
+            result,
+            done,
+            cancelled: done && cancel.load(Ordering::Relaxed),
+        };
+
+        for (index, path) in paths.iter().enumerate() {
+            if cancel.load(Ordering::Relaxed) {
+                break;
+            }
+            let mut last = Instant::now();
+            let res = hash_file(&pathenc::decode(path), &algorithms, &cancel, |n| {
+                if last.elapsed() >= PROGRESS_INTERVAL {
+                    let _ = app.emit("hash-progress", progress(index, bytes_before + n, None, false));
+                    last = Instant::now();
+                }
+            });
+            if cancel.load(Ordering::Relaxed) {
+                break;
+            }
+            bytes_before += sizes[index];
+            let result = FileHashes {
+                path: path.clone(),
+                size: sizes[index],
+                hashes: res.as_ref().cloned().unwrap_or_default(),
+                error: res.err(),
+            };
+            let _ = app.emit("hash-progress", progress(index, bytes_before, Some(result), false));
+        }
+
+        let last = paths.len().saturating_sub(1);
+        let _ = app.emit("hash-progress", progress(last, bytes_before, None, true));
+        jobs::finish(id);
+    });
+
+    Ok(id)
+}
+
+/// Stop a running hash job; its final event reports `cancelled`
+#[tauri::command]
+pub fn cancel_hashes(id: u64) -> Result<(), String> {
+    jobs::cancel(id)
+}
@@ -0,0 +1,82 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+
+// Cancel flags of running background jobs (directory sizes, hashing, ...);
+// IDs are unique across all kinds of job
+struct Jobs {
+    next_id: u64,
+    running: HashMap<u64, Arc<AtomicBool>>,
+    /// Destinations that copies and moves are writing into
+    writing: HashMap<u64, PathBuf>,
+}
+
+fn jobs() -> &'static Mutex<Jobs> {
+    static JOBS: OnceLock<Mutex<Jobs>> = OnceLock::new();
+    JOBS.get_or_init(|| {
+        Mutex::new(Jobs {
+            next_id: 1,
+            running: HashMap::new(),
+            writing: HashMap::new(),
+        })
+    })
+}
+
+/// Register a new job and return its ID and cancel flag
+pub fn start() -> Result<(u64, Arc<AtomicBool>), String> {
+    let mut jobs = jobs().lock().map_err(|e| e.to_string())?;
+    let id = jobs.next_id;
+    jobs.next_id += 1;
+    let cancel = Arc::new(AtomicBool::new(false));
+    jobs.running.insert(id, cancel.clone());
+    Ok((id, cancel))
+}
+
+/// Forget a job once it has sent its last event
+pub fn finish(id: u64) {
+    if let Ok(mut jobs) = jobs().lock() {
+        jobs.running.remove(&id);
+    }
+}
+
+/// Ask a running job to stop. A job that already finished has nothing left to cancel.
+pub fn cancel(id: u64) -> Result<(), String> {
+    let jobs = jobs().lock().map_err(|e| e.to_string())?;
+    if let Some(flag) = jobs.running.get(&id) {
+        flag.store(true, Ordering::Relaxed);
+    }
+    Ok(())
+}
+
+/// Marks a destination as being written to until dropped
+pub struct WriteGuard(Option<u64>);
+
+impl Drop for WriteGuard {
+    fn drop(&mut self) {
+        if let (Some(id), Ok(mut jobs)) = (self.0, jobs().lock()) {
+            jobs.writing.remove(&id);
+        }
+    }
+}
+
+/// Register `dest` as the target of a running copy or move
+pub fn writing(dest: &Path) -> WriteGuard {
+    let dest = dest.canonicalize().unwrap_or_else(|_| dest.to_path_buf());
+    let id = jobs().lock().ok().map(|mut jobs| {
+        let id = jobs.next_id;
+        jobs.next_id += 1;
+        jobs.writing.insert(id, dest);
+        id
+    });
+    WriteGuard(id)
+}
+
+/// Whether a copy or move is currently writing somewhere below `root`
+pub fn is_writing_under(root: &Path) -> bool {
+    let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+    jobs()
+        .lock()
+        .map(|jobs| jobs.writing.values().any(|p| p.starts_with(&root)))
+        .unwrap_or(false)
+}
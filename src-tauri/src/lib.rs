@@ -1,10 +1,41 @@
+mod acl;
+mod archive;
+mod archivefs;
 mod clipboard;
 mod commands;
+mod dircache;
+mod dirsize;
+mod drivemon;
+mod hashing;
+mod jobs;
+mod listing;
+mod manifest;
+mod metadata;
+mod mimetype;
+mod mounts;
+mod pathenc;
+mod properties;
+mod removable;
+mod spacemon;
+mod udisks;
 mod watcher;
+mod xattrs;
 
+use acl::*;
+use archive::*;
+use archivefs::*;
 use clipboard::*;
 use commands::*;
+use dirsize::*;
+use hashing::*;
+use manifest::*;
+use mimetype::*;
+use properties::*;
+use removable::*;
+use spacemon::*;
+use udisks::*;
 use watcher::*;
+use xattrs::*;
 
 use serde::{Deserialize, Serialize};
 use tauri::{
@@ -152,6 +183,12 @@ pub fn run() {
             // Setup global shortcuts
             setup_global_shortcuts(&app_handle)?;
 
+            // Emit "drives-changed" when volumes come and go
+            drivemon::start(app_handle.clone());
+
+            // Notify when the home volume or a mounted drive runs low on space
+            spacemon::start(app_handle.clone());
+
             Ok(())
         })
         .invoke_handler(tauri::generate_handler![
@@ -168,9 +205,16 @@ pub fn run() {
             open_file,
             search_files,
             get_file_details,
+            detect_file_types,
             read_file_text,
             read_file_base64,
             calculate_dir_size,
+            start_dir_size,
+            cancel_dir_size,
+            compute_hashes,
+            cancel_hashes,
+            verify_manifest,
+            create_manifest,
             clipboard_write_files,
             clipboard_read_files,
             clipboard_has_files,
@@ -181,6 +225,15 @@ pub fn run() {
             open_in_terminal,
             copy_path_to_clipboard,
             get_file_properties,
+            set_permissions,
+            set_owner,
+            set_file_times,
+            list_xattrs,
+            get_xattr,
+            set_xattr,
+            remove_xattr,
+            get_acl,
+            set_acl,
             watch_directory,
             unwatch_directory,
             quit_app,
@@ -190,10 +243,17 @@ pub fn run() {
             send_notification,
             compress_items,
             extract_archive,
+            list_archive,
+            read_archive_entry,
             list_recycle_bin,
             restore_from_recycle_bin,
             empty_recycle_bin,
             detect_usb_drives,
+            mount_volume,
+            unmount_volume,
+            eject_drive,
+            get_space_alerts,
+            set_space_alerts,
             duplicate_item,
             create_shortcut,
             batch_rename,
@@ -311,102 +371,6 @@ async fn send_notification(app: tauri::AppHandle, title: String, body: String) -
     Ok(())
 }
 
-// Compression commands
-#[tauri::command]
-async fn compress_items(paths: Vec<String>, output_path: String) -> Result<String, String> {
-    use std::fs::File;
-    use std::io::{Read, Write};
-    use walkdir::WalkDir;
-    use zip::write::SimpleFileOptions;
-    use zip::ZipWriter;
-    
-    tauri::async_runtime::spawn_blocking(move || {
-        let file = File::create(&output_path).map_err(|e| e.to_string())?;
-        let mut zip = ZipWriter::new(file);
-        let options = SimpleFileOptions::default()
-            .compression_method(zip::CompressionMethod::Deflated)
-            .unix_permissions(0o755);
-
-        for path_str in paths {
-            let path = std::path::Path::new(&path_str);
-            if path.is_dir() {
-                // Walk directory recursively
-                for entry in WalkDir::new(path).into_iter().filter_map(|e| e.ok()) {
-                    let entry_path = entry.path();
-                    let relative_path = entry_path.strip_prefix(path.parent().unwrap_or(path))
-                        .unwrap_or(entry_path);
-                    
-                    if entry_path.is_file() {
-                        zip.start_file(relative_path.to_string_lossy(), options)
-                            .map_err(|e| e.to_string())?;
-                        let mut f = File::open(entry_path).map_err(|e| e.to_string())?;
-                        let mut buffer = Vec::new();
-                        f.read_to_end(&mut buffer).map_err(|e| e.to_string())?;
-                        zip.write_all(&buffer).map_err(|e| e.to_string())?;
-                    } else if entry_path.is_dir() && entry_path != path {
-                        zip.add_directory(relative_path.to_string_lossy(), options)
-                            .map_err(|e| e.to_string())?;
-                    }
-                }
-            } else if path.is_file() {
-                let file_name = path.file_name().unwrap_or_default().to_string_lossy();
-                zip.start_file(file_name.to_string(), options)
-                    .map_err(|e| e.to_string())?;
-                let mut f = File::open(path).map_err(|e| e.to_string())?;
-                let mut buffer = Vec::new();
-                f.read_to_end(&mut buffer).map_err(|e| e.to_string())?;
-                zip.write_all(&buffer).map_err(|e| e.to_string())?;
-            }
-        }
-
-        zip.finish().map_err(|e| e.to_string())?;
-        Ok(output_path)
-    })
-    .await
-    .map_err(|e| e.to_string())?
-}
-
-#[tauri::command]
-async fn extract_archive(archive_path: String, output_dir: String) -> Result<String, String> {
-    use std::fs::{self, File};
-    use zip::ZipArchive;
-    
-    tauri::async_runtime::spawn_blocking(move || {
-        let file = File::open(&archive_path).map_err(|e| e.to_string())?;
-        let mut archive = ZipArchive::new(file).map_err(|e| e.to_string())?;
-        
-        fs::create_dir_all(&output_dir).map_err(|e| e.to_string())?;
-        
-        for i in 0..archive.len() {
-            let mut file = archive.by_index(i).map_err(|e| e.to_string())?;
-            let outpath = std::path::Path::new(&output_dir).join(file.mangled_name());
-            
-            if file.is_dir() {
-                fs::create_dir_all(&outpath).map_err(|e| e.to_string())?;
-            } else {
-                if let Some(parent) = outpath.parent() {
-                    fs::create_dir_all(parent).map_err(|e| e.to_string())?;
-                }
-                let mut outfile = File::create(&outpath).map_err(|e| e.to_string())?;
-                std::io::copy(&mut file, &mut outfile).map_err(|e| e.to_string())?;
-            }
-
-            // Set permissions on Unix
-            #[cfg(unix)]
-            {
-                use std::os::unix::fs::PermissionsExt;
-                if let Some(mode) = file.unix_mode() {
-                    fs::set_permissions(&outpath, fs::Permissions::from_mode(mode)).ok();
-                }
-            }
-        }
-        
-        Ok(output_dir)
-    })
-    .await
-    .map_err(|e| e.to_string())?
-}
-
 // Recycle Bin commands
 #[derive(Debug, Serialize, Deserialize, Clone)]
 struct RecycleBinItem {
@@ -551,75 +515,3 @@ async fn empty_recycle_bin() -> Result<(), String> {
     }
 }
 
-// USB Drive Detection
-#[derive(Debug, Serialize, Deserialize, Clone)]
-struct UsbDrive {
-    name: String,
-    path: String,
-    total_space: u64,
-    free_space: u64,
-    drive_type: String,
-}
-
-#[tauri::command]
-async fn detect_usb_drives() -> Result<Vec<UsbDrive>, String> {
-    #[cfg(windows)]
-    {
-        use std::process::Command;
-        
-        tauri::async_runtime::spawn_blocking(|| {
-            let output = Command::new("powershell")
-                .args([
-                    "-NoProfile",
-                    "-Command",
-                    r#"
-                    Get-WmiObject Win32_LogicalDisk | Where-Object { $_.DriveType -eq 2 } | 
-                    ForEach-Object {
-                        [PSCustomObject]@{
-                            Name = $_.VolumeName
-                            Path = $_.DeviceID + '\'
-                            TotalSpace = $_.Size
-                            FreeSpace = $_.FreeSpace
-                            DriveType = 'Removable'
-                        }
-                    } | ConvertTo-Json -Compress
-                    "#,
-                ])
-                .output()
-                .map_err(|e| e.to_string())?;
-
-            let output_str = String::from_utf8_lossy(&output.stdout);
-            
-            if output_str.trim().is_empty() {
-                return Ok(vec![]);
-            }
-            
-            let items: Vec<serde_json::Value> = serde_json::from_str(&output_str)
-                .unwrap_or_else(|_| {
-                    serde_json::from_str(&format!("[{}]", output_str))
-                        .unwrap_or_default()
-                });
-            
-            let result: Vec<UsbDrive> = items
-                .into_iter()
-                .map(|item| UsbDrive {
-                    name: item["Name"].as_str().unwrap_or("USB Drive").to_string(),
-                    path: item["Path"].as_str().unwrap_or("").to_string(),
-                    total_space: item["TotalSpace"].as_u64().unwrap_or(0),
-                    free_space: item["FreeSpace"].as_u64().unwrap_or(0),
-                    drive_type: "Removable".to_string(),
-                })
-                .collect();
-            
-            Ok(result)
-        })
-        .await
-        .map_err(|e| e.to_string())?
-    }
-    
-    #[cfg(not(windows))]
-    {
-        Ok(vec![])
-    }
-}
-
@@ -0,0 +1,332 @@
+use crate::commands::FileEntry;
+use icu_collator::{Collator, CollatorOptions, Numeric, Strength};
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortField {
+    #[default]
+    Name,
+    Size,
+    Modified,
+    Type,
+    Extension,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SortDirection {
+    #[default]
+    Asc,
+    Desc,
+}
+
+/// How names are compared when sorting
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Collation {
+    /// Case-insensitive comparison of the raw characters
+    #[default]
+    Simple,
+    /// Case-insensitive, digit runs compared by value ("file2" < "file10")
+    Natural,
+    /// Unicode collation for `locale` (or the system locale), with numeric digit runs
+    Locale,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KindFilter {
+    Folders,
+    Documents,
+    Images,
+    Videos,
+    Audio,
+    Archives,
+    Code,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GroupBy {
+    #[default]
+    None,
+    Type,
+    Date,
+    Size,
+}
+
+/// Options for `list_directory`; every field is optional on the JS side
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct ListOptions {
+    pub sort_by: SortField,
+    pub direction: SortDirection,
+    pub folders_first: bool,
+    pub collation: Collation,
+    /// BCP 47 tag such as "de" or "sv-SE"; defaults to `$LANG`
+    pub locale: Option<String>,
+    /// Glob patterns matched case-insensitively against the entry name (any may match)
+    pub patterns: Vec<String>,
+    /// Kind filters (any may match); empty keeps every kind
+    pub kinds: Vec<KindFilter>,
+    pub group_by: GroupBy,
+    /// Fill in `FileEntry::details` (owner, mode, inode, timestamps)
+    pub detailed: bool,
+}
+
+impl Default for ListOptions {
+    fn default() -> Self {
+        Self {
+            sort_by: SortField::Name,
+            direction: SortDirection::Asc,
+            folders_first: true,
+            collation: Collation::Simple,
+            locale: None,
+            patterns: Vec::new(),
+            kinds: Vec::new(),
+            group_by: GroupBy::None,
+            detailed: false,
+        }
+    }
+}
+
+/// A contiguous run of entries sharing the same group label
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct EntryGroup {
+    pub label: String,
+    pub start: usize,
+    pub count: usize,
+}
+
+const DOCUMENT_EXTS: &[&str] = &[
+    "pdf", "doc", "docx", "xls", "xlsx", "ppt", "pptx", "txt", "rtf", "odt", "ods", "odp",
+];
+const IMAGE_EXTS: &[&str] = &[
+    "jpg", "jpeg", "png", "gif", "bmp", "svg", "webp", "ico", "tiff",
+];
+const VIDEO_EXTS: &[&str] = &["mp4", "mkv", "avi", "mov", "wmv", "flv", "webm"];
+const AUDIO_EXTS: &[&str] = &["mp3", "wav", "flac", "aac", "ogg", "wma", "m4a"];
+const ARCHIVE_EXTS: &[&str] = &["zip", "rar", "7z", "tar", "gz", "bz2", "xz"];
+const CODE_EXTS: &[&str] = &[
+    "js", "ts", "tsx", "jsx", "py", "java", "c", "cpp", "h", "cs", "go", "rs", "rb", "php",
+    "html", "css", "scss", "json", "xml", "yaml", "yml", "md", "sql",
+];
+
+// Type group labels in display order; the last one catches everything else
+const TYPE_GROUPS: &[&str] = &[
+    "Folders", "Documents", "Images", "Videos", "Audio", "Archives", "Code", "Other Files",
+];
+
+fn type_group(entry: &FileEntry) -> usize {
+    if entry.is_dir {
+        return 0;
+    }
+    let ext = entry.extension.to_lowercase();
+    let lists = [DOCUMENT_EXTS, IMAGE_EXTS, VIDEO_EXTS, AUDIO_EXTS, ARCHIVE_EXTS, CODE_EXTS];
+    lists
+        .iter()
+        .position(|list| list.contains(&ext.as_str()))
+        .map(|i| i + 1)
+        .unwrap_or(TYPE_GROUPS.len() - 1)
+}
+
+fn matches_kind(entry: &FileEntry, kind: KindFilter) -> bool {
+    let group = type_group(entry);
+    match kind {
+        KindFilter::Folders => group == 0,
+        KindFilter::Documents => group == 1,
+        KindFilter::Images => group == 2,
+        KindFilter::Videos => group == 3,
+        KindFilter::Audio => group == 4,
+        KindFilter::Archives => group == 5,
+        KindFilter::Code => group == 6,
+    }
+}
+
+const DATE_GROUPS: &[&str] = &[
+    "Today", "Yesterday", "This Week", "This Month", "This Year", "Older", "Unknown Date",
+];
+
+fn date_group(entry: &FileEntry, today: chrono::NaiveDate) -> usize {
+    let date = match entry
+        .modified_at
+        .and_then(|t| chrono::DateTime::from_timestamp(t.secs, t.nanos))
+    {
+        Some(dt) => dt.with_timezone(&chrono::Local).date_naive(),
+        None => return DATE_GROUPS.len() - 1,
+    };
+    match (today - date).num_days() {
+        d if d <= 0 => 0,
+        1 => 1,
+        2..=7 => 2,
+        8..=30 => 3,
+        31..=365 => 4,
+        _ => 5,
+    }
+}
+
+const SIZE_GROUPS: &[&str] = &[
+    "Folders",
+    "Empty",
+    "Tiny (< 1 KB)",
+    "Small (< 100 KB)",
+    "Medium (< 1 MB)",
+    "Large (< 100 MB)",
+    "Very Large (> 100 MB)",
+];
+
+fn size_group(entry: &FileEntry) -> usize {
+    if entry.is_dir {
+        return 0;
+    }
+    match entry.size {
+        0 => 1,
+        s if s < 1024 => 2,
+        s if s < 100 * 1024 => 3,
+        s if s < 1024 * 1024 => 4,
+        s if s < 100 * 1024 * 1024 => 5,
+        _ => 6,
+    }
+}
+
+/// Compare two strings case-insensitively, treating digit runs as numbers
+pub fn natural_cmp(a: &str, b: &str) -> Ordering {
+    let mut ai = a.chars().peekable();
+    let mut bi = b.chars().peekable();
+    loop {
+        match (ai.peek().copied(), bi.peek().copied()) {
+            (None, None) => return a.cmp(b),
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) if x.is_ascii_digit() && y.is_ascii_digit() => {
+                let da: String = std::iter::from_fn(|| ai.next_if(|c| c.is_ascii_digit())).collect();
+                let db: String = std::iter::from_fn(|| bi.next_if(|c| c.is_ascii_digit())).collect();
+                let ta = da.trim_start_matches('0');
+                let tb = db.trim_start_matches('0');
+                let ord = ta
+                    .len()
+                    .cmp(&tb.len())
+                    .then_with(|| ta.cmp(tb))
+                    .then_with(|| db.len().cmp(&da.len()));
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+            }
+            (Some(x), Some(y)) => {
+                let ord = x.to_lowercase().cmp(y.to_lowercase());
+                if ord != Ordering::Equal {
+                    return ord;
+                }
+                ai.next();
+                bi.next();
+            }
+        }
+    }
+}
+
+// "de_DE.UTF-8" -> "de-DE"
+fn system_locale() -> Option<String> {
+    ["LC_ALL", "LC_COLLATE", "LANG"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|v| !v.is_empty() && v != "C" && v != "POSIX")
+        .map(|v| v.split(['.', '@']).next().unwrap_or_default().replace('_', "-"))
+}
+
+fn build_collator(locale: Option<&str>) -> Result<Collator, String> {
+    let tag = locale.map(str::to_string).or_else(system_locale).unwrap_or_default();
+    let locale: icu_locid::Locale = tag.parse().unwrap_or_default();
+    let mut options = CollatorOptions::new();
+    options.strength = Some(Strength::Tertiary);
+    options.numeric = Some(Numeric::On);
+    Collator::try_new(&(&locale).into(), options)
+        .map_err(|e| format!("Failed to create collator for '{}': {}", tag, e))
+}
+
+/// Filter, sort and group `entries` in place, returning group boundaries when grouping
+pub fn apply(entries: &mut Vec<FileEntry>, options: &ListOptions) -> Result<Option<Vec<EntryGroup>>, String> {
+    if !options.patterns.is_empty() {
+        let patterns = options
+            .patterns
+            .iter()
+            .map(|p| glob::Pattern::new(p).map_err(|e| format!("Invalid pattern '{}': {}", p, e)))
+            .collect::<Result<Vec<_>, _>>()?;
+        let match_options = glob::MatchOptions {
+            case_sensitive: false,
+            ..Default::default()
+        };
+        entries.retain(|e| patterns.iter().any(|p| p.matches_with(&e.name, match_options)));
+    }
+    if !options.kinds.is_empty() {
+        entries.retain(|e| options.kinds.iter().any(|k| matches_kind(e, *k)));
+    }
+
+    let collator = match options.collation {
+        Collation::Locale => Some(build_collator(options.locale.as_deref())?),
+        _ => None,
+    };
+    let compare_names = |a: &str, b: &str| match options.collation {
+        Collation::Simple => a.to_lowercase().cmp(&b.to_lowercase()),
+        Collation::Natural => natural_cmp(a, b),
+        Collation::Locale => collator.as_ref().map(|c| c.compare(a, b)).unwrap_or(Ordering::Equal),
+    };
+
+    let today = chrono::Local::now().date_naive();
+    let group_of = |e: &FileEntry| match options.group_by {
+        GroupBy::None => 0,
+        GroupBy::Type => type_group(e),
+        GroupBy::Date => date_group(e, today),
+        GroupBy::Size => size_group(e),
+    };
+
+    entries.sort_by(|a, b| {
+        let by_field = match options.sort_by {
+            SortField::Name => Ordering::Equal,
+            SortField::Size => a.size.cmp(&b.size),
+            SortField::Modified => a.modified_at.cmp(&b.modified_at),
+            SortField::Type => type_group(a)
+                .cmp(&type_group(b))
+                .then_with(|| compare_names(&a.extension, &b.extension)),
+            SortField::Extension => compare_names(&a.extension, &b.extension),
+        };
+        let by_field = by_field.then_with(|| compare_names(&a.name, &b.name));
+        let by_field = match options.direction {
+            SortDirection::Asc => by_field,
+            SortDirection::Desc => by_field.reverse(),
+        };
+        // Groups keep a fixed display order regardless of direction
+        let by_group = group_of(a).cmp(&group_of(b));
+        let by_folder = if options.folders_first {
+            b.is_dir.cmp(&a.is_dir)
+        } else {
+            Ordering::Equal
+        };
+        by_group.then(by_folder).then(by_field)
+    });
+
+    let labels = match options.group_by {
+        GroupBy::None => return Ok(None),
+        GroupBy::Type => TYPE_GROUPS,
+        GroupBy::Date => DATE_GROUPS,
+        GroupBy::Size => SIZE_GROUPS,
+    };
+    let mut groups: Vec<EntryGroup> = Vec::new();
+    let mut current: Option<usize> = None;
+    for (i, entry) in entries.iter().enumerate() {
+        let g = group_of(entry);
+        if current != Some(g) {
+            groups.push(EntryGroup {
+                label: labels[g].to_string(),
+                start: i,
+                count: 0,
+            });
+            current = Some(g);
+        }
+        if let Some(last) = groups.last_mut() {
+            last.count += 1;
+        }
+    }
+    Ok(Some(groups))
+}
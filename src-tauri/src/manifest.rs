@@ -0,0 +1,454 @@
+// Checksum manifests: `sha256sum`/`md5sum` output (GNU and BSD tag style)
+// and `.sfv` files. Listed paths are relative to the manifest's directory.
+use crate::hashing::{self, HashAlgorithm};
+use crate::pathenc;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+#[cfg(unix)]
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::AtomicBool;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+use tauri::{async_runtime, AppHandle, Emitter};
+use walkdir::WalkDir;
+
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(250);
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ManifestFormat {
+    /// `<hex>  <name>` as written by the coreutils *sum tools
+    Sum,
+    /// `SHA256 (<name>) = <hex>`
+    Bsd,
+    /// `<name> <crc32>`
+    Sfv,
+}
+
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CheckStatus {
+    Ok,
+    Mismatch,
+    Missing,
+    Unreadable,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ManifestCheck {
+    /// As written in the manifest
+    pub name: String,
+    pub path: String,
+    pub status: CheckStatus,
+    pub expected: String,
+    pub actual: Option<String>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ManifestReport {
+    pub manifest: String,
+    pub algorithm: HashAlgorithm,
+    pub checks: Vec<ManifestCheck>,
+    pub ok: usize,
+    pub mismatched: usize,
+    pub missing: usize,
+    pub unreadable: usize,
+    /// Non-empty, non-comment lines that could not be parsed
+    pub malformed_lines: usize,
+}
+
+/// Payload of the "manifest-progress" event
+#[derive(Debug, Serialize, Clone)]
+pub struct ManifestProgress {
+    /// The manifest being verified or written, as passed in
+    pub manifest: String,
+    pub files_done: usize,
+    pub file_count: usize,
+    pub bytes_done: u64,
+    pub bytes_total: u64,
+}
+
+struct Line {
+    name: String,
+    digest: String,
+    algorithm: Option<HashAlgorithm>,
+}
+
+fn algorithm_from_name(name: &str) -> Option<HashAlgorithm> {
+    let name = name.to_ascii_lowercase();
+    let name = name.trim_end_matches("sums").trim_end_matches("sum");
+    let ext = name.rsplit('.').next().unwrap_or(name);
+    match ext {
+        "md5" => Some(HashAlgorithm::Md5),
+        "sha1" => Some(HashAlgorithm::Sha1),
+        "sha256" => Some(HashAlgorithm::Sha256),
+        "sha512" => Some(HashAlgorithm::Sha512),
+        "b3" | "blake3" => Some(HashAlgorithm::Blake3),
+        "sfv" | "crc" | "crc32" => Some(HashAlgorithm::Crc32),
+        _ => None,
+    }
+}
+
+// Lengths are ambiguous between SHA-256 and BLAKE3; SHA-256 is far more common
+fn algorithm_from_digest(digest: &str) -> Option<HashAlgorithm> {
+    match digest.len() {
+        8 => Some(HashAlgorithm::Crc32),
+        32 => Some(HashAlgorithm::Md5),
+        40 => Some(HashAlgorithm::Sha1),
+        64 => Some(HashAlgorithm::Sha256),
+        128 => Some(HashAlgorithm::Sha512),
+        _ => None,
+    }
+}
+
+fn is_hex(s: &str) -> bool {
+    !s.is_empty() && s.bytes().all(|b| b.is_ascii_hexdigit())
+}
+
+fn bsd_line() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"^\\?([A-Za-z0-9-]+) \((.*)\) = ([0-9A-Fa-f]+)$").expect("valid regex"))
+}
+
+// Names containing a backslash or line break are escaped and the line starts with "\"
+fn unescape_name(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut chars = name.chars();
+    while let Some(c) = chars.next() {
+        match (c, chars.clone().next()) {
+            ('\\', Some('\\')) => {
+                out.push('\\');
+                chars.next();
+            }
+            ('\\', Some('n')) => {
+                out.push('\n');
+                chars.next();
+            }
+            ('\\', Some('r')) => {
+                out.push('\r');
+                chars.next();
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+fn escape_name(name: &str) -> (bool, String) {
+    let escaped = name.replace('\\', "\\\\").replace('\n', "\\n").replace('\r', "\\r");
+    (escaped != name, escaped)
+}
+
+fn parse_line(line: &str, sfv: bool) -> Option<Line> {
+    if sfv {
+        let (name, crc) = line.rsplit_once(' ')?;
+        return (is_hex(crc) && crc.len() == 8).then(|| Line {
+            name: name.trim_end().to_string(),
+            digest: crc.to_ascii_lowercase(),
+            algorithm: Some(HashAlgorithm::Crc32),
+        });
+    }
+
+    let escaped = line.starts_with('\\');
+    if let Some(caps) = bsd_line().captures(line) {
+        let name = caps[2].to_string();
+        return Some(Line {
+            name: if escaped { unescape_name(&name) } else { name },
+            digest: caps[3].to_ascii_lowercase(),
+            algorithm: algorithm_from_name(&caps[1].replace('-', "")),
+        });
+    }
+
+    let body = if escaped { &line[1..] } else { line };
+    let (digest, rest) = body.split_once(' ')?;
+    // One space and then ' ' (text mode) or '*' (binary mode) before the name
+    let name = rest.strip_prefix(' ').or_else(|| rest.strip_prefix('*'))?;
+    (is_hex(digest) && !name.is_empty()).then(|| Line {
+        name: if escaped { unescape_name(name) } else { name.to_string() },
+        digest: digest.to_ascii_lowercase(),
+        algorithm: None,
+    })
+}
+
+fn read_manifest(path: &Path) -> Result<String, String> {
+    let bytes = fs::read(path).map_err(|e| e.to_string())?;
+    // Through pathenc, so names that are not UTF-8 survive parsing
+    #[cfg(unix)]
+    let text = {
+        use std::os::unix::ffi::OsStrExt;
+        pathenc::encode(OsStr::from_bytes(&bytes))
+    };
+    #[cfg(not(unix))]
+    let text = String::from_utf8_lossy(&bytes).to_string();
+    Ok(text)
+}
+
+/// Check every file listed in a manifest. Progress arrives as "manifest-progress" events.
+#[tauri::command]
+pub async fn verify_manifest(
+    app: AppHandle,
+    path: String,
+    algorithm: Option<HashAlgorithm>,
+) -> Result<ManifestReport, String> {
+    async_runtime::spawn_blocking(move || verify(&app, &path, algorithm))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+fn verify(app: &AppHandle, manifest: &str, algorithm: Option<HashAlgorithm>) -> Result<ManifestReport, String> {
+    let manifest_path = pathenc::decode(manifest);
+    let base = manifest_path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let file_name = manifest_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let sfv = file_name.to_ascii_lowercase().ends_with(".sfv");
+
+    let mut malformed_lines = 0;
+    let lines: Vec<Line> = read_manifest(&manifest_path)?
+        .lines()
+        .map(|l| l.trim_end_matches('\r'))
+        .filter(|l| !l.trim().is_empty() && !l.starts_with(';') && !l.starts_with('#'))
+        .filter_map(|l| {
+            let parsed = parse_line(l, sfv);
+            if parsed.is_none() {
+                malformed_lines += 1;
+            }
+            parsed
+        })
+        .collect();
+
+    let algorithm = algorithm
+        .or_else(|| lines.iter().find_map(|l| l.algorithm))
+        .or_else(|| algorithm_from_name(&file_name))
+        .or_else(|| lines.first().and_then(|l| algorithm_from_digest(&l.digest)))
+        .ok_or("Could not tell which hash algorithm the manifest uses")?;
+
+    let targets: Vec<PathBuf> = lines.iter().map(|l| base.join(pathenc::decode(&l.name))).collect();
+    let sizes: Vec<u64> = targets.iter().map(|p| fs::metadata(p).map(|m| m.len()).unwrap_or(0)).collect();
+    let bytes_total = sizes.iter().sum();
+    let no_cancel = AtomicBool::new(false);
+    let mut bytes_before = 0;
+    let mut last = Instant::now();
+    let mut checks = Vec::with_capacity(lines.len());
+
+    for (i, (line, target)) in lines.iter().zip(&targets).enumerate() {
+        let mut check = ManifestCheck {
+            name: line.name.clone(),
+            path: pathenc::encode(target),
+            status: CheckStatus::Ok,
+            expected: line.digest.clone(),
+            actual: None,
+            error: None,
+        };
+        if !target.exists() {
+            check.status = CheckStatus::Missing;
+        } else {
+            let res = hashing::hash_file(target, &[algorithm], &no_cancel, |n| {
+                if last.elapsed() >= PROGRESS_INTERVAL {
+                    let _ = app.emit(
+                        "manifest-progress",
+                        ManifestProgress {
+                            manifest: manifest.to_string(),
+                            files_done: i,
+                            file_count: lines.len(),
+                            bytes_done: bytes_before + n,
+                            bytes_total,
+                        },
+                    );
+                    last = Instant::now();
+                }
+            });
+            match res.map(|mut h| h.remove(&algorithm).unwrap_or_default()) {
+                Ok(actual) => {
+                    if actual != line.digest {
+                        check.status = CheckStatus::Mismatch;
+                    }
+                    check.actual = Some(actual);
+                }
+                Err(e) => {
+                    check.status = CheckStatus::Unreadable;
+                    check.error = Some(e);
+                }
+            }
+        }
+        bytes_before += sizes[i];
+        checks.push(check);
+    }
+
+    let count = |s: CheckStatus| checks.iter().filter(|c| c.status == s).count();
+    Ok(ManifestReport {
+        manifest: manifest.to_string(),
+        algorithm,
+        ok: count(CheckStatus::Ok),
+        mismatched: count(CheckStatus::Mismatch),
+        missing: count(CheckStatus::Missing),
+        unreadable: count(CheckStatus::Unreadable),
+        checks,
+        malformed_lines,
+    })
+}
+
+fn format_line(format: ManifestFormat, algorithm: HashAlgorithm, name: &str, digest: &str) -> String {
+    match format {
+        ManifestFormat::Sfv => format!("{} {}\n", name, digest.to_ascii_uppercase()),
+        ManifestFormat::Sum | ManifestFormat::Bsd => {
+            let (escaped, name) = escape_name(name);
+            let prefix = if escaped { "\\" } else { "" };
+            if format == ManifestFormat::Sum {
+                format!("{}{}  {}\n", prefix, digest, name)
+            } else {
+                let tag = match algorithm {
+                    HashAlgorithm::Md5 => "MD5",
+                    HashAlgorithm::Sha1 => "SHA1",
+                    HashAlgorithm::Sha256 => "SHA256",
+                    HashAlgorithm::Sha512 => "SHA512",
+                    HashAlgorithm::Blake3 => "BLAKE3",
+                    HashAlgorithm::Crc32 => "CRC32",
+                };
+                format!("{}{} ({}) = {}\n", prefix, tag, name, digest)
+            }
+        }
+    }
+}
+
+fn write_manifest(path: &Path, text: &str) -> Result<(), String> {
+    // Undo pathenc so non-UTF-8 names are written back as their original bytes
+    #[cfg(unix)]
+    let bytes = {
+        use std::os::unix::ffi::OsStrExt;
+        pathenc::decode(text).as_os_str().as_bytes().to_vec()
+    };
+    #[cfg(not(unix))]
+    let bytes = text.as_bytes().to_vec();
+    fs::write(path, bytes).map_err(|e| e.to_string())
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ManifestSummary {
+    pub manifest: String,
+    pub algorithm: HashAlgorithm,
+    pub files: usize,
+    /// Files that could not be read and were left out
+    pub failed: Vec<String>,
+}
+
+/// Write a manifest for the given files, descending into directories.
+/// Names are relative to the manifest's directory where possible.
+#[tauri::command]
+pub async fn create_manifest(
+    app: AppHandle,
+    paths: Vec<String>,
+    output: String,
+    algorithm: Option<HashAlgorithm>,
+    format: Option<ManifestFormat>,
+) -> Result<ManifestSummary, String> {
+    async_runtime::spawn_blocking(move || create(&app, &paths, &output, algorithm, format))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+// Name a file by the path it was found under, relative to the manifest's folder,
+// so symlinks are listed under their own name rather than their target's
+fn manifest_name(file: &Path, bases: &[PathBuf]) -> PathBuf {
+    let resolved_parent = file
+        .parent()
+        .and_then(|p| p.canonicalize().ok())
+        .zip(file.file_name())
+        .map(|(parent, name)| parent.join(name));
+    for candidate in std::iter::once(file.to_path_buf()).chain(resolved_parent) {
+        if let Some(name) = bases.iter().find_map(|b| candidate.strip_prefix(b).ok()) {
+            return name.to_path_buf();
+        }
+    }
+    file.to_path_buf()
+}
+
+fn create(
+    app: &AppHandle,
+    paths: &[String],
+    output: &str,
+    algorithm: Option<HashAlgorithm>,
+    format: Option<ManifestFormat>,
+) -> Result<ManifestSummary, String> {
+    let out_path = pathenc::decode(output);
+    let out_name = out_path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    let algorithm = algorithm
+        .or_else(|| algorithm_from_name(&out_name))
+        .unwrap_or(HashAlgorithm::Sha256);
+    let format = format.unwrap_or(if algorithm == HashAlgorithm::Crc32 {
+        ManifestFormat::Sfv
+    } else {
+        ManifestFormat::Sum
+    });
+    if format == ManifestFormat::Sfv && algorithm != HashAlgorithm::Crc32 {
+        return Err("SFV files only hold CRC32 checksums".to_string());
+    }
+    let base = out_path.parent().map(Path::to_path_buf).unwrap_or_default();
+    let bases = [base.canonicalize().unwrap_or_else(|_| base.clone()), base];
+
+    // The manifest itself may already exist inside the tree being listed
+    let manifest = out_path.canonicalize().ok();
+    // Like sha256sum, a link and its target are both listed; only a path
+    // reached twice (a file inside a selected folder) is listed once
+    let mut seen: HashSet<PathBuf> = HashSet::new();
+    let mut files: Vec<PathBuf> = Vec::new();
+    for p in paths {
+        for entry in WalkDir::new(pathenc::decode(p)).sort_by_file_name().into_iter().flatten() {
+            let is_file = entry.file_type().is_file()
+                || (entry.path_is_symlink() && fs::metadata(entry.path()).map(|m| m.is_file()).unwrap_or(false));
+            if !is_file || (manifest.is_some() && entry.path().canonicalize().ok() == manifest) {
+                continue;
+            }
+            if seen.insert(entry.path().to_path_buf()) {
+                files.push(entry.into_path());
+            }
+        }
+    }
+
+    let sizes: Vec<u64> = files.iter().map(|f| fs::metadata(f).map(|m| m.len()).unwrap_or(0)).collect();
+    let bytes_total = sizes.iter().sum();
+    let no_cancel = AtomicBool::new(false);
+    let mut bytes_before = 0;
+    let mut last = Instant::now();
+    let mut text = String::new();
+    let mut failed = Vec::new();
+    let mut written = 0;
+
+    for (i, file) in files.iter().enumerate() {
+        let res = hashing::hash_file(file, &[algorithm], &no_cancel, |n| {
+            if last.elapsed() >= PROGRESS_INTERVAL {
+                let _ = app.emit(
+                    "manifest-progress",
+                    ManifestProgress {
+                        manifest: output.to_string(),
+                        files_done: i,
+                        file_count: files.len(),
+                        bytes_done: bytes_before + n,
+                        bytes_total,
+                    },
+                );
+                last = Instant::now();
+            }
+        });
+        bytes_before += sizes[i];
+        match res {
+            Ok(mut hashes) => {
+                let digest = hashes.remove(&algorithm).unwrap_or_default();
+                let name = manifest_name(file, &bases);
+                text.push_str(&format_line(format, algorithm, &pathenc::encode(&name), &digest));
+                written += 1;
+            }
+            Err(_) => failed.push(pathenc::encode(file)),
+        }
+    }
+
+    write_manifest(&out_path, &text)?;
+    Ok(ManifestSummary {
+        manifest: output.to_string(),
+        algorithm,
+        files: written,
+        failed,
+    })
+}
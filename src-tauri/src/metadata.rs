@@ -0,0 +1,206 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Seconds and nanoseconds since the Unix epoch (UTC); `secs` is negative before 1970
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Timestamp {
+    pub secs: i64,
+    pub nanos: u32,
+}
+
+impl Timestamp {
+    pub fn from_system_time(t: SystemTime) -> Self {
+        match t.duration_since(UNIX_EPOCH) {
+            Ok(d) => Timestamp {
+                secs: d.as_secs() as i64,
+                nanos: d.subsec_nanos(),
+            },
+            Err(e) => {
+                let d = e.duration();
+                if d.subsec_nanos() == 0 {
+                    Timestamp { secs: -(d.as_secs() as i64), nanos: 0 }
+                } else {
+                    Timestamp {
+                        secs: -(d.as_secs() as i64) - 1,
+                        nanos: 1_000_000_000 - d.subsec_nanos(),
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Modification time straight from the inode, without going through `SystemTime`
+pub fn modified_timestamp(meta: &fs::Metadata) -> Option<Timestamp> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(Timestamp {
+            secs: meta.mtime(),
+            nanos: meta.mtime_nsec() as u32,
+        })
+    }
+    #[cfg(not(unix))]
+    {
+        meta.modified().ok().map(Timestamp::from_system_time)
+    }
+}
+
+/// Extra per-entry metadata for details columns, only filled in on request
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct EntryDetails {
+    /// Full `st_mode`, including the file type bits
+    pub mode: Option<u32>,
+    /// `ls -l` style string, e.g. "drwxr-xr-x"
+    pub permissions: Option<String>,
+    pub uid: Option<u32>,
+    pub gid: Option<u32>,
+    pub owner: Option<String>,
+    pub group: Option<String>,
+    pub inode: Option<u64>,
+    pub device: Option<u64>,
+    pub nlink: Option<u64>,
+    pub link_target: Option<String>,
+    pub is_broken_link: bool,
+    pub created: Option<Timestamp>,
+    pub modified: Option<Timestamp>,
+    pub accessed: Option<Timestamp>,
+}
+
+/// Caches uid/gid name lookups across the entries of one listing
+#[derive(Default)]
+pub struct NameCache {
+    users: HashMap<u32, Option<String>>,
+    groups: HashMap<u32, Option<String>>,
+}
+
+impl NameCache {
+    pub fn user(&mut self, uid: u32) -> Option<String> {
+        self.users.entry(uid).or_insert_with(|| lookup_user(uid)).clone()
+    }
+
+    pub fn group(&mut self, gid: u32) -> Option<String> {
+        self.groups.entry(gid).or_insert_with(|| lookup_group(gid)).clone()
+    }
+}
+
+#[cfg(unix)]
+fn lookup_user(uid: u32) -> Option<String> {
+    use std::ffi::CStr;
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::passwd = std::ptr::null_mut();
+    let rc = unsafe { libc::getpwuid_r(uid, &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) };
+    if rc != 0 || result.is_null() {
+        return None;
+    }
+    let name = unsafe { CStr::from_ptr(pwd.pw_name) };
+    Some(name.to_string_lossy().to_string())
+}
+
+#[cfg(unix)]
+fn lookup_group(gid: u32) -> Option<String> {
+    use std::ffi::CStr;
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut grp: libc::group = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::group = std::ptr::null_mut();
+    let rc = unsafe { libc::getgrgid_r(gid, &mut grp, buf.as_mut_ptr(), buf.len(), &mut result) };
+    if rc != 0 || result.is_null() {
+        return None;
+    }
+    let name = unsafe { CStr::from_ptr(grp.gr_name) };
+    Some(name.to_string_lossy().to_string())
+}
+
+#[cfg(not(unix))]
+fn lookup_user(_uid: u32) -> Option<String> {
+    None
+}
+
+#[cfg(not(unix))]
+fn lookup_group(_gid: u32) -> Option<String> {
+    None
+}
+
+/// Render `st_mode` the way `ls -l` does
+#[cfg(unix)]
+pub fn mode_string(mode: u32) -> String {
+    let kind = match mode & 0o170000 {
+        0o040000 => 'd',
+        0o120000 => 'l',
+        0o020000 => 'c',
+        0o060000 => 'b',
+        0o010000 => 'p',
+        0o140000 => 's',
+        _ => '-',
+    };
+    let bit = |mask: u32, c: char| if mode & mask != 0 { c } else { '-' };
+    let exec = |x: u32, special: u32, set: char, unset: char| match (mode & x != 0, mode & special != 0) {
+        (true, true) => set,
+        (false, true) => unset,
+        (true, false) => 'x',
+        (false, false) => '-',
+    };
+    [
+        kind,
+        bit(0o400, 'r'),
+        bit(0o200, 'w'),
+        exec(0o100, 0o4000, 's', 'S'),
+        bit(0o040, 'r'),
+        bit(0o020, 'w'),
+        exec(0o010, 0o2000, 's', 'S'),
+        bit(0o004, 'r'),
+        bit(0o002, 'w'),
+        exec(0o001, 0o1000, 't', 'T'),
+    ]
+    .iter()
+    .collect()
+}
+
+/// Collect details from the entry's own (non-followed) metadata
+pub fn entry_details(path: &Path, meta: &fs::Metadata, names: &mut NameCache) -> EntryDetails {
+    // Some filesystems report a zero birth time instead of leaving it out
+    let created = meta
+        .created()
+        .ok()
+        .map(Timestamp::from_system_time)
+        .filter(|t| t.secs != 0 || t.nanos != 0);
+    let mut details = EntryDetails {
+        created,
+        modified: modified_timestamp(meta),
+        accessed: meta.accessed().ok().map(Timestamp::from_system_time),
+        ..Default::default()
+    };
+
+    if meta.is_symlink() {
+        details.link_target = fs::read_link(path).ok().map(crate::pathenc::encode);
+        details.is_broken_link = fs::metadata(path).is_err();
+    }
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        details.mode = Some(meta.mode());
+        details.permissions = Some(mode_string(meta.mode()));
+        details.uid = Some(meta.uid());
+        details.gid = Some(meta.gid());
+        details.owner = names.user(meta.uid());
+        details.group = names.group(meta.gid());
+        details.inode = Some(meta.ino());
+        details.device = Some(meta.dev());
+        details.nlink = Some(meta.nlink());
+        details.accessed = Some(Timestamp {
+            secs: meta.atime(),
+            nanos: meta.atime_nsec() as u32,
+        });
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = names;
+    }
+
+    details
+}
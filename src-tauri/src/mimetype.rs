@@ -0,0 +1,129 @@
+use crate::pathenc;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+use tauri::async_runtime;
+
+// Enough for shared-mime-info rules on common formats without reading whole files
+const SNIFF_BYTES: usize = 8 * 1024;
+
+const OCTET_STREAM: &str = "application/octet-stream";
+const TEXT_PLAIN: &str = "text/plain";
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileTypeInfo {
+    pub path: String,
+    pub mime: String,
+    /// Preview kind matching the frontend's `PreviewKind`, plus "folder"
+    pub kind: String,
+    /// "content" when sniffed from magic bytes, "extension" when guessed from the name,
+    /// "generic" when only text-vs-binary could be told apart
+    pub source: String,
+}
+
+fn is_generic(mime: &str) -> bool {
+    mime == OCTET_STREAM || mime == TEXT_PLAIN || mime == "application/x-zerosize"
+}
+
+fn is_text_like(mime: &str) -> bool {
+    mime.starts_with("text/")
+        || mime.ends_with("+xml")
+        || mime.ends_with("+json")
+        || matches!(
+            mime,
+            "application/json"
+                | "application/xml"
+                | "application/javascript"
+                | "application/x-shellscript"
+                | "application/toml"
+                | "application/x-yaml"
+                | "application/sql"
+        )
+}
+
+fn read_head(path: &Path) -> std::io::Result<Vec<u8>> {
+    let mut buf = Vec::with_capacity(SNIFF_BYTES);
+    fs::File::open(path)?
+        .take(SNIFF_BYTES as u64)
+        .read_to_end(&mut buf)?;
+    Ok(buf)
+}
+
+/// Sniff content first (system shared-mime-info, then built-in signatures),
+/// falling back to the extension only when the content is inconclusive
+pub fn detect(path: &Path) -> FileTypeInfo {
+    let info = |mime: String, source: &str| FileTypeInfo {
+        path: pathenc::encode(path),
+        kind: kind_for_mime(&mime).to_string(),
+        mime,
+        source: source.to_string(),
+    };
+
+    if path.is_dir() {
+        return info("inode/directory".to_string(), "content");
+    }
+
+    let by_extension = mime_guess::from_path(path).first().map(|m| m.essence_str().to_string());
+
+    let head = match read_head(path) {
+        Ok(head) => head,
+        Err(_) => {
+            return match by_extension {
+                Some(mime) => info(mime, "extension"),
+                None => info(OCTET_STREAM.to_string(), "generic"),
+            }
+        }
+    };
+    if head.is_empty() {
+        return match by_extension {
+            Some(mime) => info(mime, "extension"),
+            None => info(TEXT_PLAIN.to_string(), "generic"),
+        };
+    }
+
+    let sniffed = tree_magic_mini::from_u8(&head).to_string();
+    if !is_generic(&sniffed) {
+        return info(sniffed, "content");
+    }
+    if let Some(kind) = infer::get(&head) {
+        return info(kind.mime_type().to_string(), "content");
+    }
+
+    // Content only told us text vs binary; trust the extension if it agrees
+    let is_text = sniffed == TEXT_PLAIN;
+    match by_extension {
+        Some(mime) if is_text_like(&mime) == is_text => info(mime, "extension"),
+        _ => info(sniffed, "generic"),
+    }
+}
+
+/// Map a MIME type onto the frontend's preview kinds
+pub fn kind_for_mime(mime: &str) -> &'static str {
+    match mime {
+        "inode/directory" => "folder",
+        "image/svg+xml" => "svg",
+        "application/pdf" => "pdf",
+        "text/markdown" | "text/x-markdown" => "markdown",
+        "text/csv" | "text/tab-separated-values" => "csv",
+        "application/json" => "json",
+        m if m.ends_with("+json") => "json",
+        m if m.starts_with("image/") => "image",
+        m if m.starts_with("audio/") => "audio",
+        m if m.starts_with("video/") => "video",
+        "text/html" | "text/css" | "text/javascript" | "application/javascript"
+        | "application/x-shellscript" | "application/sql" | "application/x-php"
+        | "application/x-perl" | "application/x-ruby" | "application/typescript" => "code",
+        m if m.starts_with("text/x-") && !matches!(m, "text/x-log" | "text/x-readme") => "code",
+        m if is_text_like(m) => "text",
+        _ => "binary",
+    }
+}
+
+/// Detect MIME type and preview kind for a batch of paths
+#[tauri::command]
+pub async fn detect_file_types(paths: Vec<String>) -> Result<Vec<FileTypeInfo>, String> {
+    async_runtime::spawn_blocking(move || paths.iter().map(|p| detect(&pathenc::decode(p))).collect())
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+}
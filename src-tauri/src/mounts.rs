@@ -0,0 +1,243 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+/// One line of `/proc/self/mountinfo`
+#[derive(Debug, Clone)]
+pub struct MountEntry {
+    pub mount_point: PathBuf,
+    /// Path inside the source filesystem that is mounted (differs for bind mounts)
+    pub root: PathBuf,
+    pub fs_type: String,
+    pub source: String,
+    /// Per-mount options such as `ro`, `nosuid`, `noexec`
+    pub options: Vec<String>,
+    /// Options of the filesystem itself, shared by all its mounts
+    pub super_options: Vec<String>,
+}
+
+impl MountEntry {
+    pub fn read_only(&self) -> bool {
+        self.options.iter().chain(&self.super_options).any(|o| o == "ro")
+    }
+}
+
+// Filesystems whose changes can happen on another machine, so inotify never sees them
+const NETWORK_FS_TYPES: &[&str] = &[
+    "nfs", "nfs4", "cifs", "smb3", "smbfs", "ncpfs", "9p", "afs", "ceph", "glusterfs",
+    "lustre", "davfs", "fuse.sshfs", "fuse.rclone", "fuse.s3fs", "fuse.gcsfuse",
+    "fuse.curlftpfs", "fuse.smbnetfs", "fuse.davfs2",
+];
+
+pub fn is_network_fs(fs_type: &str) -> bool {
+    NETWORK_FS_TYPES.contains(&fs_type)
+}
+
+// Kernel and desktop plumbing that holds no user files
+const PSEUDO_FS_TYPES: &[&str] = &[
+    "proc", "sysfs", "devtmpfs", "devpts", "tmpfs", "ramfs", "cgroup", "cgroup2", "securityfs",
+    "pstore", "efivarfs", "debugfs", "tracefs", "configfs", "fusectl", "bpf", "mqueue",
+    "hugetlbfs", "autofs", "binfmt_misc", "rpc_pipefs", "nsfs", "selinuxfs", "nfsd",
+    "fuse.portal", "fuse.gvfsd-fuse", "fuse.lxcfs",
+];
+
+// System trees whose mounts are never drives; /run/media is where udisks mounts removable media
+const SYSTEM_PREFIXES: &[&str] = &["/proc", "/sys", "/dev", "/run", "/snap", "/var/lib/docker", "/var/snap"];
+
+/// Whether a mount is something a user would browse as a drive. A tmpfs
+/// `/tmp` counts, other tmpfs mounts do not.
+pub fn is_user_mount(m: &MountEntry) -> bool {
+    let point = &m.mount_point;
+    if m.fs_type == "tmpfs" && point == Path::new("/tmp") {
+        return true;
+    }
+    if PSEUDO_FS_TYPES.contains(&m.fs_type.as_str()) {
+        return false;
+    }
+    point.starts_with("/run/media") || !SYSTEM_PREFIXES.iter().any(|p| point.starts_with(p))
+}
+
+/// Mounts to list as drives, once per mount point (the top-most mount wins)
+pub fn user_mounts(mounts: &[MountEntry]) -> Vec<&MountEntry> {
+    let mut result: Vec<&MountEntry> = Vec::new();
+    for m in mounts.iter().filter(|m| is_user_mount(m)) {
+        result.retain(|r| r.mount_point != m.mount_point);
+        result.push(m);
+    }
+    result
+}
+
+// udev escapes unsafe characters in link names as \xHH
+fn unescape_udev(name: &str) -> String {
+    let bytes = name.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i..i + 4)
+            .filter(|d| d.starts_with(b"\\x"))
+            .and_then(|d| u8::from_str_radix(std::str::from_utf8(&d[2..]).ok()?, 16).ok());
+        if let Some(byte) = hex {
+            out.push(byte);
+            i += 4;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    String::from_utf8_lossy(&out).to_string()
+}
+
+/// Map of resolved device node to link name for a `/dev/disk/by-*` directory
+pub fn disk_links(dir: &Path) -> HashMap<PathBuf, String> {
+    std::fs::read_dir(dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter_map(|e| {
+            let device = e.path().canonicalize().ok()?;
+            Some((device, unescape_udev(&e.file_name().to_string_lossy())))
+        })
+        .collect()
+}
+
+/// Filesystem labels and UUIDs of block devices, keyed by device node
+pub struct DiskIds {
+    labels: HashMap<PathBuf, String>,
+    uuids: HashMap<PathBuf, String>,
+}
+
+impl DiskIds {
+    pub fn read() -> Self {
+        Self::read_from(Path::new("/dev/disk"))
+    }
+
+    /// Links under `dir` (normally `/dev/disk`)
+    pub fn read_from(dir: &Path) -> Self {
+        DiskIds {
+            labels: disk_links(&dir.join("by-label")),
+            uuids: disk_links(&dir.join("by-uuid")),
+        }
+    }
+
+    // Sources like /dev/mapper/x are symlinks to the /dev/dm-N nodes the links
+    // point at; network and pseudo filesystems have no device path at all
+    fn device(source: &str) -> Option<PathBuf> {
+        Path::new(source).is_absolute().then(|| Path::new(source).canonicalize().ok()).flatten()
+    }
+
+    pub fn label(&self, source: &str) -> Option<String> {
+        Self::device(source).and_then(|d| self.labels.get(&d).cloned())
+    }
+
+    pub fn uuid(&self, source: &str) -> Option<String> {
+        Self::device(source).and_then(|d| self.uuids.get(&d).cloned())
+    }
+}
+
+// mountinfo escapes space, tab, newline and backslash as \ooo; other bytes,
+// including ones that are not UTF-8, are left as they are
+fn unescape(bytes: &[u8]) -> PathBuf {
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let octal = bytes
+            .get(i + 1..i + 4)
+            .filter(|d| d.iter().all(|b| (b'0'..=b'7').contains(b)))
+            .and_then(|d| u8::from_str_radix(std::str::from_utf8(d).ok()?, 8).ok());
+        if let (b'\\', Some(byte)) = (bytes[i], octal) {
+            out.push(byte);
+            i += 4;
+        } else {
+            out.push(bytes[i]);
+            i += 1;
+        }
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        PathBuf::from(std::ffi::OsString::from_vec(out))
+    }
+    #[cfg(not(unix))]
+    {
+        PathBuf::from(String::from_utf8_lossy(&out).to_string())
+    }
+}
+
+fn text(field: &[u8]) -> String {
+    String::from_utf8_lossy(field).to_string()
+}
+
+fn list(field: &[u8]) -> Vec<String> {
+    field.split(|&b| b == b',').map(text).collect()
+}
+
+/// Parse the contents of a mountinfo file, skipping malformed lines
+pub fn parse_mountinfo(contents: &[u8]) -> Vec<MountEntry> {
+    contents
+        .split(|&b| b == b'\n')
+        .filter_map(|line| {
+            let dash = line.windows(3).position(|w| w == b" - ")?;
+            let left: Vec<&[u8]> = line[..dash].split(|&b| b == b' ').collect();
+            let mut right = line[dash + 3..].split(|&b| b == b' ');
+            if left.len() < 6 {
+                return None;
+            }
+            let fs_type = text(right.next()?);
+            let source = right.next().map(text).unwrap_or_default();
+            let options = list(left[5]);
+            let super_options = right.next().map(list).unwrap_or_default();
+            Some(MountEntry {
+                mount_point: unescape(left[4]),
+                root: unescape(left[3]),
+                fs_type,
+                source,
+                options,
+                super_options,
+            })
+        })
+        .collect()
+}
+
+/// Current mount table; empty where `/proc/self/mountinfo` does not exist
+pub fn read_mounts() -> Vec<MountEntry> {
+    std::fs::read("/proc/self/mountinfo")
+        .map(|c| parse_mountinfo(&c))
+        .unwrap_or_default()
+}
+
+/// The mount that `path` lives on: the deepest mount point containing it,
+/// with later (over-)mounts winning over earlier ones
+pub fn mount_for<'a>(mounts: &'a [MountEntry], path: &Path) -> Option<&'a MountEntry> {
+    let path = path.canonicalize().unwrap_or_else(|_| path.to_path_buf());
+    mounts
+        .iter()
+        .enumerate()
+        .filter(|(_, m)| path.starts_with(&m.mount_point))
+        .max_by_key(|(i, m)| (m.mount_point.components().count(), *i))
+        .map(|(_, m)| m)
+}
+
+/// Whether `path` is on a network filesystem, where inotify misses remote changes
+pub fn is_network_path(path: &Path) -> bool {
+    mount_for(&read_mounts(), path).is_some_and(|m| is_network_fs(&m.fs_type))
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use std::os::unix::ffi::OsStrExt;
+
+    #[test]
+    fn parses_mount_points_that_are_not_utf8() {
+        let info = b"22 1 8:1 / / rw,relatime - ext4 /dev/sda1 rw\n\
+            40 22 8:17 / /media/caf\xe9\\040disk rw,nosuid - vfat /dev/sdb1 rw,fmask=0022\n\
+            malformed line\n";
+        let mounts = parse_mountinfo(info);
+        assert_eq!(mounts.len(), 2);
+        assert_eq!(mounts[1].mount_point.as_os_str().as_bytes(), b"/media/caf\xe9 disk");
+        assert_eq!(mounts[1].fs_type, "vfat");
+        assert_eq!(mounts[1].source, "/dev/sdb1");
+        assert_eq!(mounts[1].options, ["rw", "nosuid"]);
+        assert_eq!(mounts[1].super_options, ["rw", "fmask=0022"]);
+    }
+}
@@ -0,0 +1,77 @@
+// Reversible path encoding for the IPC boundary.
+//
+// Linux filenames are arbitrary bytes, but JSON strings must be valid Unicode.
+// Valid UTF-8 passes through unchanged; every byte that is not part of valid
+// UTF-8 is mapped to a private-use code point U+F780..U+F7FF (0xF700 + byte).
+// Names that genuinely contain one of those code points have its UTF-8 bytes
+// escaped the same way, so decoding is always exact.
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+
+#[cfg(unix)]
+const ESCAPE_BASE: u32 = 0xF700;
+
+#[cfg(unix)]
+fn is_escape(c: char) -> bool {
+    (0xF780..=0xF7FF).contains(&(c as u32))
+}
+
+#[cfg(unix)]
+fn escape(byte: u8) -> char {
+    char::from_u32(ESCAPE_BASE + byte as u32).unwrap_or(char::REPLACEMENT_CHARACTER)
+}
+
+/// Encode a path or name for sending to the frontend
+#[cfg(unix)]
+pub fn encode<S: AsRef<OsStr>>(s: S) -> String {
+    use std::os::unix::ffi::OsStrExt;
+    let bytes = s.as_ref().as_bytes();
+    let mut out = String::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            if is_escape(c) {
+                let mut buf = [0u8; 4];
+                c.encode_utf8(&mut buf).bytes().for_each(|b| out.push(escape(b)));
+            } else {
+                out.push(c);
+            }
+        }
+        chunk.invalid().iter().for_each(|&b| out.push(escape(b)));
+    }
+    out
+}
+
+/// Decode a path received from the frontend back into the exact OS path
+#[cfg(unix)]
+pub fn decode(s: &str) -> PathBuf {
+    use std::os::unix::ffi::OsStringExt;
+    let mut bytes = Vec::with_capacity(s.len());
+    for c in s.chars() {
+        if is_escape(c) {
+            bytes.push((c as u32 - ESCAPE_BASE) as u8);
+        } else {
+            let mut buf = [0u8; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+        }
+    }
+    PathBuf::from(OsString::from_vec(bytes))
+}
+
+#[cfg(not(unix))]
+pub fn encode<S: AsRef<OsStr>>(s: S) -> String {
+    s.as_ref().to_string_lossy().to_string()
+}
+
+#[cfg(not(unix))]
+pub fn decode(s: &str) -> PathBuf {
+    PathBuf::from(OsString::from(s))
+}
+
+/// Human-readable form (U+FFFD for undecodable bytes), only when it differs from `encode`
+pub fn display<S: AsRef<OsStr>>(s: S) -> Option<String> {
+    let s = s.as_ref();
+    match s.to_str() {
+        Some(valid) if encode(s) == valid => None,
+        _ => Some(s.to_string_lossy().to_string()),
+    }
+}
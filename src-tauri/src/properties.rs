@@ -0,0 +1,411 @@
+use crate::metadata::Timestamp;
+use crate::pathenc;
+use filetime::FileTime;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::async_runtime;
+use walkdir::WalkDir;
+
+/// Outcome of one change to one item
+#[derive(Debug, Serialize, Clone)]
+pub struct ItemResult {
+    pub path: String,
+    pub ok: bool,
+    pub error: Option<String>,
+}
+
+impl ItemResult {
+    fn new(path: &Path, res: Result<(), String>) -> Self {
+        ItemResult {
+            path: pathenc::encode(path),
+            ok: res.is_ok(),
+            error: res.err(),
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ChmodOptions {
+    /// Octal ("755", "0644") or symbolic ("u+x,go-w", "a=rX") mode for every item
+    pub mode: Option<String>,
+    /// Overrides `mode` for files
+    pub file_mode: Option<String>,
+    /// Overrides `mode` for directories
+    pub dir_mode: Option<String>,
+    pub recursive: bool,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct ChownOptions {
+    /// User name or numeric uid; unchanged when absent
+    pub user: Option<String>,
+    /// Group name or numeric gid; unchanged when absent
+    pub group: Option<String>,
+    pub recursive: bool,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct TimesOptions {
+    /// Unchanged when absent
+    pub modified: Option<Timestamp>,
+    /// Unchanged when absent
+    pub accessed: Option<Timestamp>,
+    pub recursive: bool,
+}
+
+// Every item a change applies to: the given paths (symlinks followed) and,
+// when recursive, everything below them (symlinks not followed)
+fn targets(paths: &[String], recursive: bool) -> Vec<(PathBuf, Result<fs::Metadata, String>, bool)> {
+    let mut out = Vec::new();
+    for path in paths {
+        let root = pathenc::decode(path);
+        let meta = fs::metadata(&root).map_err(|e| e.to_string());
+        let descend = recursive && meta.as_ref().is_ok_and(|m| m.is_dir());
+        out.push((root.clone(), meta, true));
+        if descend {
+            for entry in WalkDir::new(&root).min_depth(1) {
+                match entry {
+                    Ok(e) => {
+                        let meta = e.metadata().map_err(|e| e.to_string());
+                        out.push((e.into_path(), meta, false));
+                    }
+                    Err(e) => {
+                        let path = e.path().map(Path::to_path_buf).unwrap_or_else(|| root.clone());
+                        out.push((path, Err(e.to_string()), false));
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+// One `who op perms` clause of a symbolic mode
+#[derive(Debug, Clone)]
+struct Clause {
+    // Bits of the u/g/o classes named, or None when no class was named
+    who: Option<u32>,
+    actions: Vec<(char, String)>,
+}
+
+#[derive(Debug, Clone)]
+enum ModeSpec {
+    Octal(u32),
+    Symbolic(Vec<Clause>),
+}
+
+fn parse_mode(s: &str) -> Result<ModeSpec, String> {
+    let s = s.trim();
+    if !s.is_empty() && s.chars().all(|c| c.is_digit(8)) {
+        return u32::from_str_radix(s, 8)
+            .ok()
+            .filter(|m| *m <= 0o7777)
+            .map(ModeSpec::Octal)
+            .ok_or_else(|| format!("Invalid mode: {}", s));
+    }
+
+    let mut clauses = Vec::new();
+    for part in s.split(',') {
+        let op_at = part
+            .find(['+', '-', '='])
+            .ok_or_else(|| format!("Invalid mode: {}", s))?;
+        let (who_str, rest) = part.split_at(op_at);
+        let mut who = None;
+        for c in who_str.chars() {
+            let bits = match c {
+                'u' => 0o4700,
+                'g' => 0o2070,
+                'o' => 0o1007,
+                'a' => 0o7777,
+                _ => return Err(format!("Invalid mode: {}", s)),
+            };
+            who = Some(who.unwrap_or(0) | bits);
+        }
+
+        let mut actions: Vec<(char, String)> = Vec::new();
+        for c in rest.chars() {
+            match c {
+                '+' | '-' | '=' => actions.push((c, String::new())),
+                'r' | 'w' | 'x' | 'X' | 's' | 't' | 'u' | 'g' | 'o' => match actions.last_mut() {
+                    Some((_, perms)) => perms.push(c),
+                    None => return Err(format!("Invalid mode: {}", s)),
+                },
+                _ => return Err(format!("Invalid mode: {}", s)),
+            }
+        }
+        clauses.push(Clause { who, actions });
+    }
+    Ok(ModeSpec::Symbolic(clauses))
+}
+
+#[cfg(target_os = "linux")]
+fn current_umask() -> u32 {
+    // Reading it through umask(2) would briefly change it for every thread
+    fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|s| {
+            s.lines()
+                .find_map(|l| l.strip_prefix("Umask:"))
+                .and_then(|v| u32::from_str_radix(v.trim(), 8).ok())
+        })
+        .unwrap_or(0o022)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn current_umask() -> u32 {
+    0o022
+}
+
+// New permission bits for an item, following chmod(1)
+fn apply_mode(spec: &ModeSpec, mode: u32, is_dir: bool, umask: u32) -> u32 {
+    let clauses = match spec {
+        ModeSpec::Octal(m) => return *m,
+        ModeSpec::Symbolic(c) => c,
+    };
+    let mut mode = mode & 0o7777;
+    for clause in clauses {
+        // Without a class, act like "a" but leave bits set in the umask alone
+        let (who, affected) = match clause.who {
+            Some(w) => (w, w),
+            None => (0o7777, 0o7777 & !umask),
+        };
+        for (op, perms) in &clause.actions {
+            let mut bits = 0;
+            for c in perms.chars() {
+                bits |= match c {
+                    'r' => 0o444,
+                    'w' => 0o222,
+                    'x' => 0o111,
+                    'X' if is_dir || mode & 0o111 != 0 => 0o111,
+                    's' => 0o6000,
+                    't' => 0o1000,
+                    'u' => ((mode >> 6) & 7) * 0o111,
+                    'g' => ((mode >> 3) & 7) * 0o111,
+                    'o' => (mode & 7) * 0o111,
+                    _ => 0,
+                };
+            }
+            match op {
+                '+' => mode |= bits & affected,
+                '-' => mode &= !(bits & affected),
+                _ => mode = (mode & !who) | (bits & affected),
+            }
+        }
+    }
+    mode
+}
+
+#[cfg(unix)]
+fn chmod(path: &Path, mode: u32) -> Result<(), String> {
+    use std::os::unix::fs::PermissionsExt;
+    fs::set_permissions(path, fs::Permissions::from_mode(mode)).map_err(|e| e.to_string())
+}
+
+#[cfg(not(unix))]
+fn chmod(_path: &Path, _mode: u32) -> Result<(), String> {
+    Err("Not supported on this platform".to_string())
+}
+
+#[cfg(unix)]
+fn current_mode(meta: &fs::Metadata) -> u32 {
+    use std::os::unix::fs::PermissionsExt;
+    meta.permissions().mode()
+}
+
+#[cfg(not(unix))]
+fn current_mode(_meta: &fs::Metadata) -> u32 {
+    0
+}
+
+/// Change permissions, optionally recursively with separate file and directory modes
+#[tauri::command]
+pub async fn set_permissions(paths: Vec<String>, options: ChmodOptions) -> Result<Vec<ItemResult>, String> {
+    let parse = |m: &Option<String>| m.as_deref().map(parse_mode).transpose();
+    let base = parse(&options.mode)?;
+    let file_spec = parse(&options.file_mode)?.or_else(|| base.clone());
+    let dir_spec = parse(&options.dir_mode)?.or(base);
+    if file_spec.is_none() && dir_spec.is_none() {
+        return Err("No mode given".to_string());
+    }
+
+    async_runtime::spawn_blocking(move || {
+        let umask = current_umask();
+        targets(&paths, options.recursive)
+            .into_iter()
+            .filter_map(|(path, meta, top)| {
+                let meta = match meta {
+                    Ok(m) => m,
+                    Err(e) => return Some(ItemResult::new(&path, Err(e))),
+                };
+                // Symlinks found while recursing have no permissions of their own
+                if !top && meta.is_symlink() {
+                    return None;
+                }
+                let spec = if meta.is_dir() { &dir_spec } else { &file_spec };
+                let mode = apply_mode(spec.as_ref()?, current_mode(&meta), meta.is_dir(), umask);
+                Some(ItemResult::new(&path, chmod(&path, mode)))
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))
+}
+
+#[cfg(unix)]
+pub(crate) fn resolve_user(name: &str) -> Result<u32, String> {
+    if let Ok(uid) = name.parse() {
+        return Ok(uid);
+    }
+    let cname = std::ffi::CString::new(name).map_err(|e| e.to_string())?;
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut pwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::passwd = std::ptr::null_mut();
+    let rc = unsafe { libc::getpwnam_r(cname.as_ptr(), &mut pwd, buf.as_mut_ptr(), buf.len(), &mut result) };
+    if rc != 0 || result.is_null() {
+        return Err(format!("Unknown user: {}", name));
+    }
+    Ok(pwd.pw_uid)
+}
+
+#[cfg(unix)]
+pub(crate) fn resolve_group(name: &str) -> Result<u32, String> {
+    if let Ok(gid) = name.parse() {
+        return Ok(gid);
+    }
+    let cname = std::ffi::CString::new(name).map_err(|e| e.to_string())?;
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut grp: libc::group = unsafe { std::mem::zeroed() };
+    let mut result: *mut libc::group = std::ptr::null_mut();
+    let rc = unsafe { libc::getgrnam_r(cname.as_ptr(), &mut grp, buf.as_mut_ptr(), buf.len(), &mut result) };
+    if rc != 0 || result.is_null() {
+        return Err(format!("Unknown group: {}", name));
+    }
+    Ok(grp.gr_gid)
+}
+
+/// Change owner and/or group (chown/chgrp). Only root may give files away;
+/// owners may switch to a group they belong to. Failures are reported per item.
+#[cfg(unix)]
+#[tauri::command]
+pub async fn set_owner(paths: Vec<String>, options: ChownOptions) -> Result<Vec<ItemResult>, String> {
+    let uid = options.user.as_deref().map(resolve_user).transpose()?;
+    let gid = options.group.as_deref().map(resolve_group).transpose()?;
+    if uid.is_none() && gid.is_none() {
+        return Err("No owner or group given".to_string());
+    }
+
+    async_runtime::spawn_blocking(move || {
+        targets(&paths, options.recursive)
+            .into_iter()
+            .map(|(path, meta, top)| {
+                let res = meta.and_then(|_| {
+                    // Links inside the tree are changed themselves, never their targets
+                    let res = if top {
+                        std::os::unix::fs::chown(&path, uid, gid)
+                    } else {
+                        std::os::unix::fs::lchown(&path, uid, gid)
+                    };
+                    res.map_err(|e| e.to_string())
+                });
+                ItemResult::new(&path, res)
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))
+}
+
+#[cfg(not(unix))]
+#[tauri::command]
+pub async fn set_owner(_paths: Vec<String>, _options: ChownOptions) -> Result<Vec<ItemResult>, String> {
+    Err("Not supported on this platform".to_string())
+}
+
+fn file_time(t: Timestamp) -> FileTime {
+    FileTime::from_unix_time(t.secs, t.nanos)
+}
+
+/// Set modified and/or accessed times
+#[tauri::command]
+pub async fn set_file_times(paths: Vec<String>, options: TimesOptions) -> Result<Vec<ItemResult>, String> {
+    if options.modified.is_none() && options.accessed.is_none() {
+        return Err("No time given".to_string());
+    }
+
+    async_runtime::spawn_blocking(move || {
+        targets(&paths, options.recursive)
+            .into_iter()
+            .map(|(path, meta, top)| {
+                let res = meta.and_then(|meta| {
+                    let link = !top && meta.is_symlink();
+                    // Whichever time is not being set keeps its current value
+                    let atime = options
+                        .accessed
+                        .map(file_time)
+                        .unwrap_or_else(|| FileTime::from_last_access_time(&meta));
+                    let mtime = options
+                        .modified
+                        .map(file_time)
+                        .unwrap_or_else(|| FileTime::from_last_modification_time(&meta));
+                    let res = if link {
+                        filetime::set_symlink_file_times(&path, atime, mtime)
+                    } else {
+                        filetime::set_file_times(&path, atime, mtime)
+                    };
+                    res.map_err(|e| e.to_string())
+                });
+                ItemResult::new(&path, res)
+            })
+            .collect()
+    })
+    .await
+    .map_err(|e| format!("Task failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chmod_to(spec: &str, mode: u32, is_dir: bool) -> u32 {
+        apply_mode(&parse_mode(spec).unwrap(), mode, is_dir, 0o022)
+    }
+
+    #[test]
+    fn symbolic_modes() {
+        let cases = [
+            ("u+x,g-w,o=r", 0o664, false, 0o744),
+            ("a+X", 0o644, false, 0o644),
+            ("a+X", 0o744, false, 0o755),
+            ("a+X", 0o644, true, 0o755),
+            ("u=rwx,go=", 0o644, false, 0o700),
+            ("g=u", 0o740, false, 0o770),
+            ("o+g-w", 0o750, true, 0o755),
+            ("u+s,+t", 0o755, true, 0o5755),
+            // No class: bits in the umask are left alone
+            ("+w", 0o444, false, 0o644),
+            ("+x", 0o644, false, 0o755),
+            ("=r", 0o777, false, 0o444),
+        ];
+        for (spec, mode, is_dir, expected) in cases {
+            assert_eq!(chmod_to(spec, mode, is_dir), expected, "{} on {:o}", spec, mode);
+        }
+    }
+
+    #[test]
+    fn octal_modes() {
+        assert_eq!(chmod_to("755", 0o600, false), 0o755);
+        assert_eq!(chmod_to("04711", 0o600, false), 0o4711);
+        assert_eq!(chmod_to("0", 0o777, true), 0);
+    }
+
+    #[test]
+    fn invalid_modes() {
+        for spec in ["", "8", "10000", "u", "z+x", "u+q", "u+x,", "+x,g"] {
+            assert!(parse_mode(spec).is_err(), "{:?} should be rejected", spec);
+        }
+    }
+}
@@ -0,0 +1,317 @@
+// Removable and USB volumes. On Linux they come from sysfs and /dev, which
+// are read from roots passed in so fake trees can stand in for them.
+use serde::{Deserialize, Serialize};
+#[cfg(target_os = "linux")]
+use std::fs;
+#[cfg(target_os = "linux")]
+use std::path::{Path, PathBuf};
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct UsbDrive {
+    pub name: String,
+    /// Mount point; empty while the volume is not mounted
+    pub path: String,
+    pub total_space: u64,
+    pub free_space: u64,
+    pub drive_type: String,
+    /// Device node such as /dev/sdb1 (Linux only)
+    pub device: Option<String>,
+    /// "usb", "mmc", "ata", ... as far as sysfs tells
+    pub bus: Option<String>,
+    pub mounted: bool,
+    pub fs_type: Option<String>,
+    pub uuid: Option<String>,
+}
+
+#[cfg(target_os = "linux")]
+fn read_trimmed(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string()).filter(|s| !s.is_empty())
+}
+
+// Partitions smaller than this are extended-partition stubs and the like
+#[cfg(target_os = "linux")]
+const MIN_VOLUME_BYTES: u64 = 1024 * 1024;
+
+// The bus a block device hangs off, from the components of its sysfs device path
+#[cfg(target_os = "linux")]
+fn bus_type(device_path: &Path) -> Option<String> {
+    let names: Vec<String> = device_path
+        .components()
+        .map(|c| c.as_os_str().to_string_lossy().to_string())
+        .collect();
+    let has = |prefix: &str| names.iter().any(|n| n.starts_with(prefix));
+    ["usb", "mmc", "nvme", "ata", "virtio", "firewire"]
+        .into_iter()
+        .find(|bus| has(bus))
+        .map(str::to_string)
+}
+
+// Volumes of one disk: its partitions, or the disk itself when it has none
+#[cfg(target_os = "linux")]
+fn volumes(disk_dir: &Path, disk: &str) -> Vec<(String, u64)> {
+    let size = |dir: &Path| {
+        read_trimmed(&dir.join("size"))
+            .and_then(|s| s.parse::<u64>().ok())
+            .unwrap_or(0)
+            * 512
+    };
+    let mut parts: Vec<(String, u64)> = fs::read_dir(disk_dir)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .filter(|e| e.path().join("partition").exists())
+        .map(|e| (e.file_name().to_string_lossy().to_string(), size(&e.path())))
+        .collect();
+    parts.sort();
+    if parts.is_empty() {
+        parts.push((disk.to_string(), size(disk_dir)));
+    }
+    parts.retain(|(_, bytes)| *bytes >= MIN_VOLUME_BYTES);
+    parts
+}
+
+/// Removable and USB volumes under `sys_root` (normally `/sys`), mounted or not,
+/// with device nodes under `dev_root` (normally `/dev`).
+/// Mounted ones report the filesystem's capacity, others the partition size.
+#[cfg(target_os = "linux")]
+pub fn removable_volumes(sys_root: &Path, dev_root: &Path, mounts: &[crate::mounts::MountEntry]) -> Vec<UsbDrive> {
+    let ids = crate::mounts::DiskIds::read_from(&dev_root.join("disk"));
+    let block = sys_root.join("block");
+    let mut disks: Vec<PathBuf> = fs::read_dir(&block)
+        .into_iter()
+        .flatten()
+        .flatten()
+        .map(|e| e.path())
+        .collect();
+    disks.sort();
+
+    let mut drives = Vec::new();
+    for disk_dir in disks {
+        // Loop, ram and device-mapper devices have no backing device
+        if !disk_dir.join("device").exists() {
+            continue;
+        }
+        let disk = disk_dir.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        let bus = bus_type(&disk_dir.canonicalize().unwrap_or_else(|_| disk_dir.clone()));
+        let removable = read_trimmed(&disk_dir.join("removable")).as_deref() == Some("1");
+        if !removable && bus.as_deref() != Some("usb") {
+            continue;
+        }
+        let model = [read_trimmed(&disk_dir.join("device/vendor")), read_trimmed(&disk_dir.join("device/model"))]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ");
+
+        for (name, size) in volumes(&disk_dir, &disk) {
+            let node = dev_root.join(&name);
+            let device = node.to_string_lossy().to_string();
+            // Sources may be symlinks such as /dev/disk/by-uuid/...
+            let resolve = |p: &Path| p.canonicalize().unwrap_or_else(|_| p.to_path_buf());
+            let node = resolve(&node);
+            let mount = mounts.iter().rev().find(|m| resolve(Path::new(&m.source)) == node);
+            let space = mount.and_then(|m| crate::commands::statvfs_space(&m.mount_point).ok());
+            let label = ids.label(&device);
+            drives.push(UsbDrive {
+                name: label
+                    .or_else(|| (!model.is_empty()).then(|| model.clone()))
+                    .unwrap_or_else(|| name.clone()),
+                path: mount.map(|m| crate::pathenc::encode(&m.mount_point)).unwrap_or_default(),
+                total_space: space.as_ref().map_or(size, |s| s.total_bytes),
+                free_space: space.as_ref().map_or(0, |s| s.available_bytes),
+                drive_type: if removable { "Removable" } else { "External" }.to_string(),
+                device: Some(device.clone()),
+                bus: bus.clone(),
+                mounted: mount.is_some(),
+                fs_type: mount.map(|m| m.fs_type.clone()),
+                uuid: ids.uuid(&device),
+            });
+        }
+    }
+    drives
+}
+
+#[tauri::command]
+pub async fn detect_usb_drives() -> Result<Vec<UsbDrive>, String> {
+    #[cfg(windows)]
+    {
+        use std::process::Command;
+
+        tauri::async_runtime::spawn_blocking(|| {
+            let output = Command::new("powershell")
+                .args([
+                    "-NoProfile",
+                    "-Command",
+                    r#"
+                    Get-WmiObject Win32_LogicalDisk | Where-Object { $_.DriveType -eq 2 } |
+                    ForEach-Object {
+                        [PSCustomObject]@{
+                            Name = $_.VolumeName
+                            Path = $_.DeviceID + '\'
+                            TotalSpace = $_.Size
+                            FreeSpace = $_.FreeSpace
+                            DriveType = 'Removable'
+                        }
+                    } | ConvertTo-Json -Compress
+                    "#,
+                ])
+                .output()
+                .map_err(|e| e.to_string())?;
+
+            let output_str = String::from_utf8_lossy(&output.stdout);
+
+            if output_str.trim().is_empty() {
+                return Ok(vec![]);
+            }
+
+            let items: Vec<serde_json::Value> = serde_json::from_str(&output_str)
+                .unwrap_or_else(|_| {
+                    serde_json::from_str(&format!("[{}]", output_str))
+                        .unwrap_or_default()
+                });
+
+            let result: Vec<UsbDrive> = items
+                .into_iter()
+                .map(|item| UsbDrive {
+                    name: item["Name"].as_str().unwrap_or("USB Drive").to_string(),
+                    path: item["Path"].as_str().unwrap_or("").to_string(),
+                    total_space: item["TotalSpace"].as_u64().unwrap_or(0),
+                    free_space: item["FreeSpace"].as_u64().unwrap_or(0),
+                    drive_type: "Removable".to_string(),
+                    mounted: true,
+                    ..Default::default()
+                })
+                .collect();
+
+            Ok(result)
+        })
+        .await
+        .map_err(|e| e.to_string())?
+    }
+
+    #[cfg(target_os = "linux")]
+    {
+        tauri::async_runtime::spawn_blocking(|| {
+            removable_volumes(Path::new("/sys"), Path::new("/dev"), &crate::mounts::read_mounts())
+        })
+        .await
+        .map_err(|e| format!("Task failed: {}", e))
+    }
+
+    #[cfg(not(any(windows, target_os = "linux")))]
+    {
+        Ok(vec![])
+    }
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use crate::mounts::MountEntry;
+    use std::os::unix::fs::symlink;
+
+    // Disk directory under a fake sysfs device tree, linked from block/
+    fn disk(sys: &Path, device_path: &str, name: &str, removable: bool, sectors: u64) -> PathBuf {
+        let dir = sys.join("devices").join(device_path).join("block").join(name);
+        fs::create_dir_all(dir.join("device")).unwrap();
+        fs::write(dir.join("removable"), if removable { "1\n" } else { "0\n" }).unwrap();
+        fs::write(dir.join("size"), format!("{}\n", sectors)).unwrap();
+        fs::create_dir_all(sys.join("block")).unwrap();
+        symlink(&dir, sys.join("block").join(name)).unwrap();
+        dir
+    }
+
+    fn partition(disk_dir: &Path, name: &str, sectors: u64) {
+        let dir = disk_dir.join(name);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(dir.join("partition"), "1\n").unwrap();
+        fs::write(dir.join("size"), format!("{}\n", sectors)).unwrap();
+    }
+
+    fn mount(source: &Path, mount_point: &Path) -> MountEntry {
+        MountEntry {
+            mount_point: mount_point.to_path_buf(),
+            root: PathBuf::from("/"),
+            fs_type: "vfat".to_string(),
+            source: source.to_string_lossy().to_string(),
+            options: vec!["rw".to_string()],
+            super_options: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn finds_removable_and_usb_volumes() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (sys, dev) = (tmp.path().join("sys"), tmp.path().join("dev"));
+
+        // Internal disk, card reader slot with two partitions, and a USB disk without any
+        disk(&sys, "pci0000:00/ata1/host0", "sda", false, 1 << 30);
+        let card = disk(&sys, "pci0000:00/mmc0/mmc0:0001", "mmcblk0", true, 1 << 22);
+        partition(&card, "mmcblk0p1", 1 << 21);
+        partition(&card, "mmcblk0p2", 2);
+        disk(&sys, "pci0000:00/usb1/1-1/1-1:1.0/host6", "sdb", false, 1 << 21);
+        fs::create_dir_all(sys.join("block/loop0")).unwrap();
+
+        fs::create_dir_all(dev.join("disk/by-label")).unwrap();
+        fs::create_dir_all(dev.join("disk/by-uuid")).unwrap();
+        for node in ["sda", "mmcblk0p1", "sdb"] {
+            fs::write(dev.join(node), "").unwrap();
+        }
+        symlink("../../sdb", dev.join("disk/by-label/BACKUP\\x20USB")).unwrap();
+        symlink("../../sdb", dev.join("disk/by-uuid/1234-ABCD")).unwrap();
+
+        let mount_point = tmp.path().join("media");
+        fs::create_dir(&mount_point).unwrap();
+        let mounts = [mount(&dev.join("mmcblk0p1"), &mount_point)];
+
+        let drives = removable_volumes(&sys, &dev, &mounts);
+        let devices: Vec<String> = drives.iter().filter_map(|d| d.device.clone()).collect();
+        let expected: Vec<String> = ["mmcblk0p1", "sdb"]
+            .iter()
+            .map(|n| dev.join(n).to_string_lossy().to_string())
+            .collect();
+        assert_eq!(devices, expected);
+
+        let card = &drives[0];
+        assert_eq!(card.bus.as_deref(), Some("mmc"));
+        assert_eq!(card.drive_type, "Removable");
+        assert!(card.mounted);
+        assert_eq!(card.path, mount_point.to_string_lossy());
+        assert_eq!(card.fs_type.as_deref(), Some("vfat"));
+        assert_eq!(card.name, "mmcblk0p1");
+
+        let usb = &drives[1];
+        assert_eq!(usb.bus.as_deref(), Some("usb"));
+        assert_eq!(usb.drive_type, "External");
+        assert!(!usb.mounted);
+        assert_eq!(usb.path, "");
+        assert_eq!(usb.total_space, (1 << 21) * 512);
+        assert_eq!(usb.name, "BACKUP USB");
+        assert_eq!(usb.uuid.as_deref(), Some("1234-ABCD"));
+    }
+
+    #[test]
+    fn matches_mounts_through_symlinked_sources() {
+        let tmp = tempfile::tempdir().unwrap();
+        let (sys, dev) = (tmp.path().join("sys"), tmp.path().join("dev"));
+        let stick = disk(&sys, "pci0000:00/usb2/2-1/2-1:1.0/host7", "sdc", true, 1 << 22);
+        partition(&stick, "sdc1", 1 << 21);
+        fs::create_dir_all(dev.join("disk/by-uuid")).unwrap();
+        fs::write(dev.join("sdc1"), "").unwrap();
+        symlink("../../sdc1", dev.join("disk/by-uuid/abcd")).unwrap();
+
+        // The mount table may name the device through one of its links
+        let (old, new) = (tmp.path().join("old"), tmp.path().join("new"));
+        fs::create_dir(&old).unwrap();
+        fs::create_dir(&new).unwrap();
+        let mounts = [
+            mount(&dev.join("disk/by-uuid/abcd"), &old),
+            mount(&dev.join("disk/by-uuid/abcd"), &new),
+        ];
+        let drives = removable_volumes(&sys, &dev, &mounts);
+        assert_eq!(drives.len(), 1);
+        assert!(drives[0].mounted);
+        assert_eq!(drives[0].path, new.to_string_lossy());
+        assert_eq!(drives[0].uuid.as_deref(), Some("abcd"));
+    }
+}
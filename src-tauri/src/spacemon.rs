@@ -0,0 +1,196 @@
+// Background check of free space on the home volume and the mounted drives.
+// Each volume alerts once when it drops below a threshold and is re-armed
+// when it has room again.
+use crate::commands::{get_drive_space, get_system_drives, DriveSpaceInfo};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::sync::{Condvar, Mutex, OnceLock};
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+const CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+#[serde(default)]
+pub struct SpaceAlertOptions {
+    pub enabled: bool,
+    /// A volume is low once both limits are crossed, so small volumes such as
+    /// /boot/efi are not flagged just for their size
+    pub min_free_percent: f64,
+    pub min_free_bytes: u64,
+}
+
+impl Default for SpaceAlertOptions {
+    fn default() -> Self {
+        SpaceAlertOptions {
+            enabled: true,
+            min_free_percent: 10.0,
+            min_free_bytes: 1024 * 1024 * 1024,
+        }
+    }
+}
+
+/// Payload of the "low-space" event
+#[derive(Debug, Serialize, Clone)]
+pub struct LowSpaceEvent {
+    pub path: String,
+    pub available_bytes: u64,
+    pub total_bytes: u64,
+    pub percent_free: f64,
+}
+
+// The options, and whether the frontend has sent its saved ones yet. The
+// condvar wakes the monitor once they arrive.
+fn options() -> &'static (Mutex<(SpaceAlertOptions, bool)>, Condvar) {
+    static OPTIONS: OnceLock<(Mutex<(SpaceAlertOptions, bool)>, Condvar)> = OnceLock::new();
+    OPTIONS.get_or_init(|| (Mutex::new((SpaceAlertOptions::default(), false)), Condvar::new()))
+}
+
+#[tauri::command]
+pub fn get_space_alerts() -> Result<SpaceAlertOptions, String> {
+    options().0.lock().map(|o| o.0.clone()).map_err(|e| e.to_string())
+}
+
+/// Change the thresholds; they apply from the next check
+#[tauri::command]
+pub fn set_space_alerts(options: SpaceAlertOptions) -> Result<(), String> {
+    let (lock, received) = self::options();
+    *lock.lock().map_err(|e| e.to_string())? = (options, true);
+    received.notify_all();
+    Ok(())
+}
+
+// Block until the frontend has sent the user's options, so a user who turned
+// alerts off is not alerted at startup with the defaults
+fn wait_for_options() {
+    let (lock, received) = options();
+    if let Ok(guard) = lock.lock() {
+        drop(received.wait_while(guard, |o| !o.1));
+    }
+}
+
+fn percent_free(space: &DriveSpaceInfo) -> f64 {
+    if space.total_bytes > 0 {
+        space.available_bytes as f64 / space.total_bytes as f64 * 100.0
+    } else {
+        100.0
+    }
+}
+
+fn is_low(space: &DriveSpaceInfo, options: &SpaceAlertOptions) -> bool {
+    // Pseudo and empty filesystems report no blocks at all
+    space.total_bytes > 0
+        && space.available_bytes < options.min_free_bytes
+        && percent_free(space) < options.min_free_percent
+}
+
+// Volumes to watch, one path per filesystem
+fn volumes() -> Vec<DriveSpaceInfo> {
+    let mut paths: Vec<String> = dirs::home_dir().map(|h| crate::pathenc::encode(&h)).into_iter().collect();
+    paths.extend(get_system_drives().unwrap_or_default().into_iter().map(|d| d.path));
+
+    let mut seen = HashSet::new();
+    paths
+        .into_iter()
+        .filter(|p| {
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::MetadataExt;
+                std::fs::metadata(crate::pathenc::decode(p)).is_ok_and(|m| seen.insert(m.dev()))
+            }
+            #[cfg(not(unix))]
+            {
+                seen.insert(p.clone())
+            }
+        })
+        .filter_map(|p| get_drive_space(p).ok())
+        .collect()
+}
+
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+fn alert(app: &AppHandle, space: &DriveSpaceInfo) {
+    use tauri_plugin_notification::NotificationExt;
+    let event = LowSpaceEvent {
+        path: space.path.clone(),
+        available_bytes: space.available_bytes,
+        total_bytes: space.total_bytes,
+        percent_free: percent_free(space),
+    };
+    let _ = app
+        .notification()
+        .builder()
+        .title("Low disk space")
+        .body(format!(
+            "{}: {} free ({:.0}%)",
+            event.path,
+            format_bytes(event.available_bytes),
+            event.percent_free
+        ))
+        .show();
+    let _ = app.emit("low-space", event);
+}
+
+/// Start the monitor thread; called once from setup
+pub fn start(app: AppHandle) {
+    std::thread::spawn(move || {
+        wait_for_options();
+        // Volumes that already alerted and have not recovered yet
+        let mut low: HashSet<String> = HashSet::new();
+        loop {
+            let options = get_space_alerts().unwrap_or_default();
+            if options.enabled {
+                for space in volumes() {
+                    if !is_low(&space, &options) {
+                        low.remove(&space.path);
+                    } else if low.insert(space.path.clone()) {
+                        alert(&app, &space);
+                    }
+                }
+            } else {
+                low.clear();
+            }
+            std::thread::sleep(CHECK_INTERVAL);
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn space(total_mb: u64, available_mb: u64) -> DriveSpaceInfo {
+        const MB: u64 = 1024 * 1024;
+        DriveSpaceInfo {
+            path: "/mnt".to_string(),
+            total_bytes: total_mb * MB,
+            free_bytes: available_mb * MB,
+            available_bytes: available_mb * MB,
+            used_bytes: (total_mb - available_mb) * MB,
+            percent_used: 0.0,
+            total_inodes: 0,
+            free_inodes: 0,
+        }
+    }
+
+    #[test]
+    fn needs_both_limits() {
+        let options = SpaceAlertOptions::default();
+        // A small ESP or USB stick with plenty of room left
+        assert!(!is_low(&space(512, 400), &options));
+        // A big disk with a lot of bytes but a small share free
+        assert!(!is_low(&space(4 * 1024 * 1024, 200 * 1024), &options));
+        assert!(is_low(&space(512, 20), &options));
+        assert!(is_low(&space(100 * 1024, 900), &options));
+        assert!(!is_low(&space(0, 0), &options));
+    }
+}
@@ -0,0 +1,438 @@
+// Mounting and safe removal through UDisks2, the D-Bus service desktop file
+// managers use; it takes care of the polkit checks for unprivileged users.
+// The helpers take the connection so they can run against any bus; the
+// commands use the system bus.
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+
+#[cfg(target_os = "linux")]
+use std::collections::HashMap;
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+#[cfg(target_os = "linux")]
+use zbus::blocking::{Connection, Proxy};
+#[cfg(target_os = "linux")]
+use zbus::zvariant::{OwnedObjectPath, Value};
+
+const UDISKS: &str = "org.freedesktop.UDisks2";
+const MANAGER_PATH: &str = "/org/freedesktop/UDisks2/Manager";
+const MANAGER_IFACE: &str = "org.freedesktop.UDisks2.Manager";
+const BLOCK_IFACE: &str = "org.freedesktop.UDisks2.Block";
+const FILESYSTEM_IFACE: &str = "org.freedesktop.UDisks2.Filesystem";
+const DRIVE_IFACE: &str = "org.freedesktop.UDisks2.Drive";
+
+const BUSY_OPEN: &str = "Volume is busy: a file on it is still open";
+const BUSY_WRITING: &str = "Volume is busy: a copy or move is still writing to it";
+
+#[cfg(target_os = "linux")]
+type Options<'a> = HashMap<&'a str, Value<'a>>;
+
+#[cfg(target_os = "linux")]
+fn dbus_error(e: zbus::Error) -> String {
+    match &e {
+        zbus::Error::MethodError(name, msg, _) => match name.as_str() {
+            "org.freedesktop.UDisks2.Error.DeviceBusy" => BUSY_OPEN.to_string(),
+            "org.freedesktop.UDisks2.Error.NotAuthorized"
+            | "org.freedesktop.UDisks2.Error.NotAuthorizedCanObtain"
+            | "org.freedesktop.UDisks2.Error.NotAuthorizedDismissed" => "Not authorized".to_string(),
+            "org.freedesktop.UDisks2.Error.AlreadyMounted" => "Volume is already mounted".to_string(),
+            "org.freedesktop.UDisks2.Error.NotMounted" => "Volume is not mounted".to_string(),
+            "org.freedesktop.DBus.Error.ServiceUnknown" => "UDisks2 is not available".to_string(),
+            _ => msg.clone().unwrap_or_else(|| name.to_string()),
+        },
+        _ => e.to_string(),
+    }
+}
+
+#[cfg(target_os = "linux")]
+fn proxy<'a>(conn: &Connection, path: OwnedObjectPath, iface: &'static str) -> Result<Proxy<'a>, String> {
+    zbus::blocking::proxy::Builder::new(conn)
+        .destination(UDISKS)
+        .and_then(|b| b.path(path))
+        .and_then(|b| b.interface(iface))
+        .map(|b| b.cache_properties(zbus::proxy::CacheProperties::No))
+        .and_then(|b| b.build())
+        .map_err(dbus_error)
+}
+
+#[cfg(target_os = "linux")]
+fn manager(conn: &Connection) -> Result<Proxy<'static>, String> {
+    let path = OwnedObjectPath::try_from(MANAGER_PATH).map_err(|e| e.to_string())?;
+    proxy(conn, path, MANAGER_IFACE)
+}
+
+// UDisks2 object of a block device such as /dev/sdb1
+#[cfg(target_os = "linux")]
+fn block_object(conn: &Connection, device: &str) -> Result<OwnedObjectPath, String> {
+    let spec: Options = HashMap::from([("path", Value::from(device))]);
+    let found: Vec<OwnedObjectPath> = manager(conn)?
+        .call("ResolveDevice", &(spec, Options::new()))
+        .map_err(dbus_error)?;
+    found.into_iter().next().ok_or_else(|| format!("Unknown device: {}", device))
+}
+
+#[cfg(target_os = "linux")]
+fn mount_points(conn: &Connection, block: &OwnedObjectPath) -> Vec<PathBuf> {
+    use std::os::unix::ffi::OsStringExt;
+    let points: Vec<Vec<u8>> = proxy(conn, block.clone(), FILESYSTEM_IFACE)
+        .and_then(|p| p.get_property("MountPoints").map_err(|e| e.to_string()))
+        .unwrap_or_default();
+    // Byte strings with a trailing NUL
+    points
+        .into_iter()
+        .map(|mut p| {
+            if p.last() == Some(&0) {
+                p.pop();
+            }
+            PathBuf::from(std::ffi::OsString::from_vec(p))
+        })
+        .collect()
+}
+
+/// Mount a block device and return where it was mounted
+#[cfg(target_os = "linux")]
+pub fn mount(conn: &Connection, device: &str) -> Result<String, String> {
+    let block = block_object(conn, device)?;
+    proxy(conn, block, FILESYSTEM_IFACE)?
+        .call("Mount", &(Options::new(),))
+        .map_err(dbus_error)
+}
+
+#[cfg(target_os = "linux")]
+fn unmount_block(conn: &Connection, block: OwnedObjectPath) -> Result<(), String> {
+    if mount_points(conn, &block).iter().any(|p| crate::jobs::is_writing_under(p)) {
+        return Err(BUSY_WRITING.to_string());
+    }
+    proxy(conn, block, FILESYSTEM_IFACE)?
+        .call::<_, _, ()>("Unmount", &(Options::new(),))
+        .map_err(dbus_error)
+}
+
+/// Unmount a volume, given its device or its mount point
+#[cfg(target_os = "linux")]
+pub fn unmount(conn: &Connection, target: &str) -> Result<(), String> {
+    let device = if target.starts_with("/dev/") {
+        target.to_string()
+    } else {
+        let point = crate::pathenc::decode(target);
+        crate::mounts::read_mounts()
+            .into_iter()
+            .rev()
+            .find(|m| m.mount_point == point)
+            .map(|m| m.source)
+            .ok_or_else(|| format!("Not a mount point: {}", target))?
+    };
+    unmount_block(conn, block_object(conn, &device)?)
+}
+
+/// Unmount every filesystem on the drive holding `device`, then eject the
+/// media and power the drive down where it supports that
+#[cfg(target_os = "linux")]
+pub fn eject(conn: &Connection, device: &str) -> Result<(), String> {
+    let block = block_object(conn, device)?;
+    let drive: OwnedObjectPath = proxy(conn, block, BLOCK_IFACE)?
+        .get_property("Drive")
+        .map_err(|e| e.to_string())?;
+    if drive.as_str() == "/" {
+        return Err(format!("{} is not on a removable drive", device));
+    }
+
+    let blocks: Vec<OwnedObjectPath> = manager(conn)?
+        .call("GetBlockDevices", &(Options::new(),))
+        .map_err(dbus_error)?;
+    for b in blocks {
+        let on_drive = proxy(conn, b.clone(), BLOCK_IFACE)?
+            .get_property::<OwnedObjectPath>("Drive")
+            .is_ok_and(|d| d == drive);
+        if on_drive && !mount_points(conn, &b).is_empty() {
+            unmount_block(conn, b)?;
+        }
+    }
+
+    let drive = proxy(conn, drive, DRIVE_IFACE)?;
+    if drive.get_property::<bool>("Ejectable").unwrap_or(false) {
+        drive.call::<_, _, ()>("Eject", &(Options::new(),)).map_err(dbus_error)?;
+    }
+    if drive.get_property::<bool>("CanPowerOff").unwrap_or(false) {
+        drive.call::<_, _, ()>("PowerOff", &(Options::new(),)).map_err(dbus_error)?;
+    }
+    Ok(())
+}
+
+#[cfg(target_os = "linux")]
+fn system_bus() -> Result<Connection, String> {
+    Connection::system().map_err(|e| format!("Cannot reach the system bus: {}", e))
+}
+
+/// Mount a removable volume by device node; returns the mount point
+#[cfg(target_os = "linux")]
+#[tauri::command]
+pub async fn mount_volume(device: String) -> Result<String, String> {
+    tauri::async_runtime::spawn_blocking(move || mount(&system_bus()?, &device))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Unmount a volume by device node or mount point
+#[cfg(target_os = "linux")]
+#[tauri::command]
+pub async fn unmount_volume(path: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || unmount(&system_bus()?, &path))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+/// Safely remove the drive holding `device`
+#[cfg(target_os = "linux")]
+#[tauri::command]
+pub async fn eject_drive(device: String) -> Result<(), String> {
+    tauri::async_runtime::spawn_blocking(move || eject(&system_bus()?, &device))
+        .await
+        .map_err(|e| format!("Task failed: {}", e))?
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+pub async fn mount_volume(_device: String) -> Result<String, String> {
+    Err("Not supported on this platform".to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+pub async fn unmount_volume(_path: String) -> Result<(), String> {
+    Err("Not supported on this platform".to_string())
+}
+
+#[cfg(not(target_os = "linux"))]
+#[tauri::command]
+pub async fn eject_drive(_device: String) -> Result<(), String> {
+    Err("Not supported on this platform".to_string())
+}
+
+// Runs the helpers against a fake UDisks2 on a private session bus
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::*;
+    use std::io::BufRead;
+    use std::path::Path;
+    use std::process::{Child, Command, Stdio};
+    use std::sync::{Arc, Mutex};
+    use zbus::zvariant::OwnedValue;
+
+    const STICK_BLOCK: &str = "/org/freedesktop/UDisks2/block_devices/sdb1";
+    const SYSTEM_BLOCK: &str = "/org/freedesktop/UDisks2/block_devices/sda1";
+    const STICK_DRIVE: &str = "/org/freedesktop/UDisks2/drives/Stick";
+
+    #[derive(Default)]
+    struct Disk {
+        mount_point: PathBuf,
+        mounted: bool,
+        busy: bool,
+        calls: Vec<&'static str>,
+    }
+
+    type Shared = Arc<Mutex<Disk>>;
+    type RawOptions = HashMap<String, OwnedValue>;
+
+    #[derive(zbus::DBusError, Debug)]
+    #[zbus(prefix = "org.freedesktop.UDisks2.Error")]
+    enum FakeError {
+        #[zbus(error)]
+        ZBus(zbus::Error),
+        DeviceBusy(String),
+    }
+
+    fn object_path(path: &str) -> OwnedObjectPath {
+        OwnedObjectPath::try_from(path).unwrap()
+    }
+
+    struct Manager;
+
+    #[zbus::interface(name = "org.freedesktop.UDisks2.Manager")]
+    impl Manager {
+        fn resolve_device(&self, spec: RawOptions, _options: RawOptions) -> Vec<OwnedObjectPath> {
+            let path: String = spec["path"].try_clone().unwrap().try_into().unwrap();
+            match path.as_str() {
+                "/dev/sdb1" => vec![object_path(STICK_BLOCK)],
+                "/dev/sda1" => vec![object_path(SYSTEM_BLOCK)],
+                _ => Vec::new(),
+            }
+        }
+
+        fn get_block_devices(&self, _options: RawOptions) -> Vec<OwnedObjectPath> {
+            vec![object_path(SYSTEM_BLOCK), object_path(STICK_BLOCK)]
+        }
+    }
+
+    struct Block(&'static str);
+
+    #[zbus::interface(name = "org.freedesktop.UDisks2.Block")]
+    impl Block {
+        #[zbus(property)]
+        fn drive(&self) -> OwnedObjectPath {
+            object_path(self.0)
+        }
+    }
+
+    struct Filesystem(Shared);
+
+    #[zbus::interface(name = "org.freedesktop.UDisks2.Filesystem")]
+    impl Filesystem {
+        fn mount(&self, _options: RawOptions) -> String {
+            let mut disk = self.0.lock().unwrap();
+            disk.mounted = true;
+            disk.calls.push("Mount");
+            disk.mount_point.to_string_lossy().to_string()
+        }
+
+        fn unmount(&self, _options: RawOptions) -> Result<(), FakeError> {
+            let mut disk = self.0.lock().unwrap();
+            if disk.busy {
+                return Err(FakeError::DeviceBusy("target is busy".to_string()));
+            }
+            disk.mounted = false;
+            disk.calls.push("Unmount");
+            Ok(())
+        }
+
+        #[zbus(property)]
+        fn mount_points(&self) -> Vec<Vec<u8>> {
+            let disk = self.0.lock().unwrap();
+            if !disk.mounted {
+                return Vec::new();
+            }
+            let mut point = disk.mount_point.to_string_lossy().as_bytes().to_vec();
+            point.push(0);
+            vec![point]
+        }
+    }
+
+    struct Drive(Shared);
+
+    #[zbus::interface(name = "org.freedesktop.UDisks2.Drive")]
+    impl Drive {
+        fn eject(&self, _options: RawOptions) {
+            self.0.lock().unwrap().calls.push("Eject");
+        }
+
+        fn power_off(&self, _options: RawOptions) {
+            self.0.lock().unwrap().calls.push("PowerOff");
+        }
+
+        #[zbus(property)]
+        fn ejectable(&self) -> bool {
+            true
+        }
+
+        #[zbus(property)]
+        fn can_power_off(&self) -> bool {
+            true
+        }
+    }
+
+    // A private dbus-daemon with the fake service on it, stopped on drop
+    struct Bus {
+        daemon: Child,
+        _service: Connection,
+        client: Connection,
+        disk: Shared,
+        _dir: tempfile::TempDir,
+    }
+
+    impl Bus {
+        // Fails rather than skips without dbus-daemon, so the tests cannot pass untested
+        fn start() -> Bus {
+            let mut daemon = Command::new("dbus-daemon")
+                .args(["--session", "--nofork", "--print-address=1"])
+                .stdout(Stdio::piped())
+                .stderr(Stdio::null())
+                .spawn()
+                .expect("the UDisks2 tests need dbus-daemon");
+            let mut address = String::new();
+            std::io::BufReader::new(daemon.stdout.take().unwrap())
+                .read_line(&mut address)
+                .unwrap();
+            let address = address.trim();
+
+            let dir = tempfile::tempdir().unwrap();
+            let disk: Shared = Arc::new(Mutex::new(Disk {
+                mount_point: dir.path().to_path_buf(),
+                ..Disk::default()
+            }));
+            let service = zbus::blocking::connection::Builder::address(address)
+                .unwrap()
+                .name(UDISKS)
+                .unwrap()
+                .serve_at(MANAGER_PATH, Manager)
+                .unwrap()
+                .serve_at(STICK_BLOCK, Block(STICK_DRIVE))
+                .unwrap()
+                .serve_at(STICK_BLOCK, Filesystem(disk.clone()))
+                .unwrap()
+                .serve_at(SYSTEM_BLOCK, Block("/"))
+                .unwrap()
+                .serve_at(STICK_DRIVE, Drive(disk.clone()))
+                .unwrap()
+                .build()
+                .unwrap();
+            let client = zbus::blocking::connection::Builder::address(address)
+                .unwrap()
+                .build()
+                .unwrap();
+            Bus {
+                daemon,
+                _service: service,
+                client,
+                disk,
+                _dir: dir,
+            }
+        }
+
+        fn calls(&self) -> Vec<&'static str> {
+            self.disk.lock().unwrap().calls.clone()
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    #[test]
+    fn mounts_through_filesystem_interface() {
+        let bus = Bus::start();
+        let point = mount(&bus.client, "/dev/sdb1").unwrap();
+        assert_eq!(Path::new(&point), bus.disk.lock().unwrap().mount_point);
+        assert_eq!(bus.calls(), ["Mount"]);
+        assert_eq!(mount(&bus.client, "/dev/sdz1").unwrap_err(), "Unknown device: /dev/sdz1");
+    }
+
+    #[test]
+    fn unmounts_and_reports_open_files() {
+        let bus = Bus::start();
+        mount(&bus.client, "/dev/sdb1").unwrap();
+        bus.disk.lock().unwrap().busy = true;
+        assert_eq!(unmount(&bus.client, "/dev/sdb1").unwrap_err(), BUSY_OPEN);
+        bus.disk.lock().unwrap().busy = false;
+        unmount(&bus.client, "/dev/sdb1").unwrap();
+        assert_eq!(bus.calls(), ["Mount", "Unmount"]);
+    }
+
+    #[test]
+    fn ejects_unless_a_copy_is_writing() {
+        let bus = Bus::start();
+        mount(&bus.client, "/dev/sdb1").unwrap();
+        let target = bus.disk.lock().unwrap().mount_point.join("incoming");
+        std::fs::create_dir(&target).unwrap();
+        {
+            let _writing = crate::jobs::writing(&target);
+            assert_eq!(eject(&bus.client, "/dev/sdb1").unwrap_err(), BUSY_WRITING);
+        }
+        assert_eq!(bus.calls(), ["Mount"]);
+
+        eject(&bus.client, "/dev/sdb1").unwrap();
+        assert_eq!(bus.calls(), ["Mount", "Unmount", "Eject", "PowerOff"]);
+        assert!(eject(&bus.client, "/dev/sda1").is_err());
+    }
+}
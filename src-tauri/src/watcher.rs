@@ -1,75 +1,384 @@
-use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
-use std::path::PathBuf;
+use crate::commands::{build_file_entry_with, FileEntry};
+use crate::dirsize;
+use crate::mounts;
+use crate::pathenc;
+use notify::{PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use notify_debouncer_mini::{new_debouncer_opt, Config, DebounceEventResult, Debouncer};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex, OnceLock};
+use std::time::Duration;
 use tauri::{AppHandle, Emitter};
+use walkdir::WalkDir;
+
+// How long a path has to stay quiet before its changes are reported
+const DEBOUNCE: Duration = Duration::from_millis(300);
+
+// Default scan interval for the polling backend
+pub(crate) const DEFAULT_POLL_INTERVAL_MS: u64 = 2000;
+const MIN_POLL_INTERVAL_MS: u64 = 250;
+
+#[derive(Debug, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "lowercase")]
+pub enum WatchBackend {
+    /// inotify & co., or polling when the directory is on a network mount
+    #[default]
+    Auto,
+    Native,
+    Poll,
+}
+
+#[derive(Debug, Deserialize, Clone, Default)]
+#[serde(default)]
+pub struct WatchOptions {
+    /// Also report changes in subdirectories (tree views, search result folders)
+    pub recursive: bool,
+    pub backend: WatchBackend,
+    pub poll_interval_ms: Option<u64>,
+}
+
+/// A single typed change inside a watched directory
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum FsChange {
+    Created { entry: FileEntry },
+    Modified { entry: FileEntry },
+    Removed { path: String },
+    Renamed { from: String, entry: FileEntry },
+}
+
+/// Payload of the "fs-changed" event
+#[derive(Debug, Serialize, Clone)]
+pub struct FsChangedEvent {
+    /// The watched directory, exactly as passed to `watch_directory`
+    pub path: String,
+    /// Sent by a recursive watch; changes may be anywhere below `path`
+    pub recursive: bool,
+    pub changes: Vec<FsChange>,
+    /// The changes could not be worked out; re-list the directory instead
+    pub rescan: bool,
+}
+
+// Last known children (or all descendants, when recursive) of a watched
+// directory with their inodes, used to tell creations from modifications and to
+// pair a removal with a creation of the same inode into a rename
+struct Snapshot {
+    children: HashMap<PathBuf, Option<u64>>,
+    recursive: bool,
+}
+
+fn inode(meta: &fs::Metadata) -> Option<u64> {
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::MetadataExt;
+        Some(meta.ino())
+    }
+    #[cfg(not(unix))]
+    {
+        let _ = meta;
+        None
+    }
+}
+
+// Every path below `dir` (not following symlinks), or just its children
+fn scan(dir: &Path, recursive: bool) -> impl Iterator<Item = (PathBuf, Option<u64>)> {
+    let depth = if recursive { usize::MAX } else { 1 };
+    WalkDir::new(dir)
+        .min_depth(1)
+        .max_depth(depth)
+        .into_iter()
+        .flatten()
+        .map(|e| {
+            let ino = e.metadata().ok().and_then(|m| inode(&m));
+            (e.into_path(), ino)
+        })
+}
+
+impl Snapshot {
+    fn read(dir: &Path, recursive: bool) -> Self {
+        Snapshot {
+            children: scan(dir, recursive).collect(),
+            recursive,
+        }
+    }
+
+    fn covers(&self, dir: &Path, path: &Path) -> bool {
+        if self.recursive {
+            path != dir && path.starts_with(dir)
+        } else {
+            path.parent() == Some(dir)
+        }
+    }
+
+    // Turn a batch of debounced paths into typed changes, updating the snapshot
+    fn apply(&mut self, dir: &Path, paths: Vec<PathBuf>) -> Vec<FsChange> {
+        let mut created: Vec<(FileEntry, Option<u64>)> = Vec::new();
+        let mut removed: Vec<(PathBuf, Option<u64>)> = Vec::new();
+        let mut changes: Vec<FsChange> = Vec::new();
+
+        for path in paths {
+            if !self.covers(dir, &path) {
+                continue;
+            }
+            let known = self.children.get(&path).copied();
+            let current = fs::symlink_metadata(&path)
+                .ok()
+                .and_then(|meta| build_file_entry_with(&path, None).ok().map(|e| (e, inode(&meta))));
+            match (known, current) {
+                (Some(_), Some((entry, ino))) => {
+                    self.children.insert(path, ino);
+                    changes.push(FsChange::Modified { entry });
+                }
+                (None, Some((entry, ino))) => {
+                    self.children.insert(path, ino);
+                    created.push((entry, ino));
+                }
+                (Some(ino), None) => {
+                    self.children.remove(&path);
+                    removed.push((path, ino));
+                }
+                // Appeared and vanished again within one debounce window
+                (None, None) => {}
+            }
+        }
+
+        if self.recursive {
+            // Whole subtrees can move in or out with a single event for their root
+            for (path, _) in &removed {
+                self.children.retain(|p, _| !p.starts_with(path));
+            }
+            for (entry, _) in created.iter().filter(|(e, _)| e.is_dir && !e.is_symlink) {
+                self.children.extend(scan(&pathenc::decode(&entry.path), true));
+            }
+        }
+
+        for (entry, ino) in created {
+            let source = ino.and_then(|ino| removed.iter().position(|(_, r)| *r == Some(ino)));
+            match source {
+                Some(i) => {
+                    let (from, _) = removed.swap_remove(i);
+                    changes.push(FsChange::Renamed {
+                        from: pathenc::encode(&from),
+                        entry,
+                    });
+                }
+                None => changes.push(FsChange::Created { entry }),
+            }
+        }
+        changes.extend(removed.into_iter().map(|(path, _)| FsChange::Removed {
+            path: pathenc::encode(&path),
+        }));
+        changes
+    }
+}
+
+// Watchers are shared by subscriptions asking for the same directory in the same mode
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct WatchKey {
+    dir: PathBuf,
+    recursive: bool,
+    /// Polling interval, or None for the native backend
+    poll: Option<Duration>,
+}
+
+enum Backend {
+    Native(Debouncer<RecommendedWatcher>),
+    Poll(Debouncer<PollWatcher>),
+}
+
+impl Backend {
+    fn watcher(&mut self) -> &mut dyn Watcher {
+        match self {
+            Backend::Native(d) => d.watcher(),
+            Backend::Poll(d) => d.watcher(),
+        }
+    }
+}
+
+struct WatchedDir {
+    _backend: Backend,
+    subscribers: usize,
+}
 
 struct WatcherState {
-    watcher: Option<RecommendedWatcher>,
-    watched_path: Option<String>,
+    next_id: u64,
+    subscriptions: HashMap<u64, WatchKey>,
+    dirs: HashMap<WatchKey, WatchedDir>,
+}
+
+impl WatcherState {
+    fn add_subscription(&mut self, key: WatchKey) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.subscriptions.insert(id, key);
+        id
+    }
 }
 
 fn state() -> &'static Arc<Mutex<WatcherState>> {
     static STATE: OnceLock<Arc<Mutex<WatcherState>>> = OnceLock::new();
     STATE.get_or_init(|| {
         Arc::new(Mutex::new(WatcherState {
-            watcher: None,
-            watched_path: None,
+            next_id: 1,
+            subscriptions: HashMap::new(),
+            dirs: HashMap::new(),
         }))
     })
 }
 
-#[tauri::command]
-pub fn watch_directory(app: AppHandle, path: String) -> Result<(), String> {
-    let mut st = state().lock().map_err(|e| e.to_string())?;
+fn create_watcher(app: AppHandle, key: &WatchKey, path: String) -> Result<Backend, String> {
+    let watched_dir = key.dir.clone();
+    let recursive = key.recursive;
+    let mut snapshot = Snapshot::read(&key.dir, recursive);
+
+    let handler = move |res: DebounceEventResult| {
+        let event = match res {
+            Ok(events) => {
+                if !watched_dir.is_dir() {
+                    FsChangedEvent {
+                        path: path.clone(),
+                        recursive,
+                        changes: Vec::new(),
+                        rescan: true,
+                    }
+                } else {
+                    let paths: Vec<PathBuf> = events.into_iter().map(|e| e.path).collect();
+                    dirsize::invalidate(&paths);
+                    FsChangedEvent {
+                        path: path.clone(),
+                        recursive,
+                        changes: snapshot.apply(&watched_dir, paths),
+                        rescan: false,
+                    }
+                }
+            }
+            Err(e) => {
+                eprintln!("Watch error: {}", e);
+                snapshot = Snapshot::read(&watched_dir, recursive);
+                FsChangedEvent {
+                    path: path.clone(),
+                    recursive,
+                    changes: Vec::new(),
+                    rescan: true,
+                }
+            }
+        };
+        if event.rescan || !event.changes.is_empty() {
+            let _ = app.emit("fs-changed", event);
+        }
+    };
 
-    // If already watching this exact path, skip
-    if st.watched_path.as_deref() == Some(&path) && st.watcher.is_some() {
-        return Ok(());
+    let config = Config::default().with_timeout(DEBOUNCE);
+    let mut backend = match key.poll {
+        Some(interval) => {
+            let config = config.with_notify_config(notify::Config::default().with_poll_interval(interval));
+            new_debouncer_opt::<_, PollWatcher>(config, handler).map(Backend::Poll)
+        }
+        None => new_debouncer_opt::<_, RecommendedWatcher>(config, handler).map(Backend::Native),
     }
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
 
-    // Drop previous watcher
-    st.watcher = None;
-    st.watched_path = None;
+    let mode = if recursive {
+        RecursiveMode::Recursive
+    } else {
+        RecursiveMode::NonRecursive
+    };
+    backend
+        .watcher()
+        .watch(&key.dir, mode)
+        .map_err(|e| format!("Failed to watch directory: {}", e))?;
+    Ok(backend)
+}
 
-    let dir = PathBuf::from(&path);
-    if !dir.is_dir() {
-        return Err(format!("Not a directory: {}", path));
-    }
+/// Start watching `path` and return a subscription ID for `unwatch_directory`.
+/// Several subscriptions (tabs, split panes) may share the same directory.
+/// Directories on network mounts are polled unless a backend is forced.
+#[tauri::command]
+pub async fn watch_directory(app: AppHandle, path: String, options: Option<WatchOptions>) -> Result<u64, String> {
+    tauri::async_runtime::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let dir = pathenc::decode(&path);
+        if !dir.is_dir() {
+            return Err(format!("Not a directory: {}", path));
+        }
 
-    let app_handle = app.clone();
-    let watched = path.clone();
+        let poll = match options.backend {
+            WatchBackend::Native => false,
+            WatchBackend::Poll => true,
+            WatchBackend::Auto => mounts::is_network_path(&dir),
+        };
+        let key = WatchKey {
+            dir,
+            recursive: options.recursive,
+            poll: poll.then(|| {
+                let ms = options.poll_interval_ms.unwrap_or(DEFAULT_POLL_INTERVAL_MS);
+                Duration::from_millis(ms.max(MIN_POLL_INTERVAL_MS))
+            }),
+        };
 
-    let watcher = RecommendedWatcher::new(
-        move |res: Result<Event, notify::Error>| {
-            match res {
-                Ok(_event) => {
-                    // Emit a simple "fs-changed" event to the frontend
-                    let _ = app_handle.emit("fs-changed", &watched);
-                }
-                Err(e) => {
-                    eprintln!("Watch error: {}", e);
-                }
+        if let Some(id) = subscribe(&key)? {
+            return Ok(id);
+        }
+
+        // Snapshotting a recursive watch walks the whole tree, so do it unlocked
+        let backend = create_watcher(app, &key, path)?;
+        let mut st = state().lock().map_err(|e| e.to_string())?;
+        let duplicate = match st.dirs.get_mut(&key) {
+            // Another call set up the same watch in the meantime
+            Some(watched) => {
+                watched.subscribers += 1;
+                Some(backend)
             }
-        },
-        Config::default(),
-    )
-    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+            None => {
+                st.dirs.insert(
+                    key.clone(),
+                    WatchedDir {
+                        _backend: backend,
+                        subscribers: 1,
+                    },
+                );
+                None
+            }
+        };
+        let id = st.add_subscription(key);
+        drop(st);
+        drop(duplicate);
+        Ok(id)
+    })
+    .await
+    .map_err(|e| e.to_string())?
+}
 
-    // We create it, then watch (RecommendedWatcher needs &mut)
-    st.watcher = Some(watcher);
-    if let Some(ref mut w) = st.watcher {
-        w.watch(&dir, RecursiveMode::NonRecursive)
-            .map_err(|e| format!("Failed to watch directory: {}", e))?;
+// Join an existing watch for `key`, if there is one
+fn subscribe(key: &WatchKey) -> Result<Option<u64>, String> {
+    let mut st = state().lock().map_err(|e| e.to_string())?;
+    match st.dirs.get_mut(key) {
+        Some(watched) => watched.subscribers += 1,
+        None => return Ok(None),
     }
-    st.watched_path = Some(path);
-
-    Ok(())
+    Ok(Some(st.add_subscription(key.clone())))
 }
 
+/// Drop a subscription; the watcher stops once its last subscriber is gone
 #[tauri::command]
-pub fn unwatch_directory() -> Result<(), String> {
+pub fn unwatch_directory(id: u64) -> Result<(), String> {
     let mut st = state().lock().map_err(|e| e.to_string())?;
-    st.watcher = None;
-    st.watched_path = None;
+    let key = st
+        .subscriptions
+        .remove(&id)
+        .ok_or_else(|| format!("Unknown watch subscription: {}", id))?;
+
+    let last = match st.dirs.get_mut(&key) {
+        Some(watched) => {
+            watched.subscribers -= 1;
+            watched.subscribers == 0
+        }
+        None => false,
+    };
+    if last {
+        st.dirs.remove(&key);
+    }
     Ok(())
 }
@@ -0,0 +1,144 @@
+#[cfg(unix)]
+use crate::pathenc;
+#[cfg(unix)]
+use base64::Engine;
+use serde::Serialize;
+use std::path::Path;
+
+/// One extended attribute. `value` is always base64 so binary data survives
+/// the IPC boundary; `text` is filled in when the value is readable text.
+#[derive(Debug, Serialize, Clone)]
+pub struct Xattr {
+    pub name: String,
+    pub namespace: String,
+    pub value: String,
+    pub text: Option<String>,
+    pub size: usize,
+    /// Only `user.*` attributes can be changed from here
+    pub writable: bool,
+}
+
+#[cfg(unix)]
+fn namespace(name: &str) -> &str {
+    name.split_once('.').map(|(ns, _)| ns).unwrap_or("")
+}
+
+#[cfg(unix)]
+fn as_text(value: &[u8]) -> Option<String> {
+    // C tools often store the terminating NUL along with the string
+    let value = value.strip_suffix(&[0]).unwrap_or(value);
+    let text = std::str::from_utf8(value).ok()?;
+    if text.chars().any(|c| c.is_control() && !matches!(c, '\n' | '\r' | '\t')) {
+        return None;
+    }
+    Some(text.to_string())
+}
+
+#[cfg(unix)]
+fn make_xattr(name: String, value: Vec<u8>) -> Xattr {
+    let ns = namespace(&name).to_string();
+    Xattr {
+        writable: ns == "user",
+        namespace: ns,
+        text: as_text(&value),
+        size: value.len(),
+        value: base64::engine::general_purpose::STANDARD.encode(&value),
+        name,
+    }
+}
+
+#[cfg(unix)]
+fn check_writable(name: &str) -> Result<(), String> {
+    if namespace(name) == "user" && name.len() > "user.".len() {
+        Ok(())
+    } else {
+        Err(format!("Only user.* attributes can be changed: {}", name))
+    }
+}
+
+/// All attributes of a file with their values (symlinks are followed).
+/// Filesystems without xattr support simply have none.
+#[cfg(unix)]
+pub fn read_all(path: &Path) -> Vec<Xattr> {
+    let Ok(names) = xattr::list_deref(path) else {
+        return Vec::new();
+    };
+    let mut attrs: Vec<Xattr> = names
+        .filter_map(|name| {
+            // Values we may not read (e.g. trusted.* without privileges) are skipped
+            let value = xattr::get_deref(path, &name).ok()??;
+            Some(make_xattr(pathenc::encode(&name), value))
+        })
+        .collect();
+    attrs.sort_by(|a, b| a.name.cmp(&b.name));
+    attrs
+}
+
+#[cfg(not(unix))]
+pub fn read_all(_path: &Path) -> Vec<Xattr> {
+    Vec::new()
+}
+
+#[cfg(unix)]
+#[tauri::command]
+pub fn list_xattrs(path: String) -> Result<Vec<Xattr>, String> {
+    let p = pathenc::decode(&path);
+    // Unlike `read_all`, a missing file is an error here
+    std::fs::metadata(&p).map_err(|e| e.to_string())?;
+    Ok(read_all(&p))
+}
+
+#[cfg(unix)]
+#[tauri::command]
+pub fn get_xattr(path: String, name: String) -> Result<Xattr, String> {
+    let value = xattr::get_deref(pathenc::decode(&path), pathenc::decode(&name))
+        .map_err(|e| e.to_string())?
+        .ok_or_else(|| format!("No such attribute: {}", name))?;
+    Ok(make_xattr(name, value))
+}
+
+/// Set a `user.*` attribute. `encoding` is "text" (default) or "base64" for binary values.
+#[cfg(unix)]
+#[tauri::command]
+pub fn set_xattr(path: String, name: String, value: String, encoding: Option<String>) -> Result<(), String> {
+    check_writable(&name)?;
+    let bytes = match encoding.as_deref().unwrap_or("text") {
+        "text" => value.into_bytes(),
+        "base64" => base64::engine::general_purpose::STANDARD
+            .decode(value)
+            .map_err(|e| format!("Invalid base64 value: {}", e))?,
+        other => return Err(format!("Unknown encoding: {}", other)),
+    };
+    xattr::set_deref(pathenc::decode(&path), pathenc::decode(&name), &bytes).map_err(|e| e.to_string())
+}
+
+#[cfg(unix)]
+#[tauri::command]
+pub fn remove_xattr(path: String, name: String) -> Result<(), String> {
+    check_writable(&name)?;
+    xattr::remove_deref(pathenc::decode(&path), pathenc::decode(&name)).map_err(|e| e.to_string())
+}
+
+#[cfg(not(unix))]
+#[tauri::command]
+pub fn list_xattrs(_path: String) -> Result<Vec<Xattr>, String> {
+    Err("Not supported on this platform".to_string())
+}
+
+#[cfg(not(unix))]
+#[tauri::command]
+pub fn get_xattr(_path: String, _name: String) -> Result<Xattr, String> {
+    Err("Not supported on this platform".to_string())
+}
+
+#[cfg(not(unix))]
+#[tauri::command]
+pub fn set_xattr(_path: String, _name: String, _value: String, _encoding: Option<String>) -> Result<(), String> {
+    Err("Not supported on this platform".to_string())
+}
+
+#[cfg(not(unix))]
+#[tauri::command]
+pub fn remove_xattr(_path: String, _name: String) -> Result<(), String> {
+    Err("Not supported on this platform".to_string())
+}
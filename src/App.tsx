@@ -28,14 +28,31 @@ import { useFileSystem } from "./hooks/useFileSystem";
 import { useSettings } from "./hooks/useSettings";
 import "./styles/debug.css";
 import type {
+  ArchiveError,
+  ArchiveFormat,
+  CompressOptions,
   ContextMenuAction,
+  DriveItem,
   ExplorerTab,
   FileEntry,
   FileFilterType,
   GroupBy,
   ViewMode,
 } from "./types";
+import { compressionLevelFor } from "./utils/compression";
 import { debugLogger } from "./utils/debugLogger";
+import { isArchiveName } from "./utils/fileClassification";
+
+const COMPRESS_FORMATS: {
+  label: string;
+  format: ArchiveFormat;
+  extension: string;
+}[] = [
+  { label: "ZIP", format: "zip", extension: ".zip" },
+  { label: "tar.gz", format: "tar_gz", extension: ".tar.gz" },
+  { label: "tar.xz", format: "tar_xz", extension: ".tar.xz" },
+  { label: "tar.zst", format: "tar_zst", extension: ".tar.zst" },
+];
 
 const App: React.FC = () => {
   const fs = useFileSystem();
@@ -333,6 +350,19 @@ const App: React.FC = () => {
       fs.navigateTo(recycleBinPath);
     }
   }, [tabs, fs]);
+
+  // Unmounts every volume on the drive; the sidebar refreshes on "drives-changed"
+  const handleEjectDrive = useCallback(
+    async (drive: DriveItem) => {
+      if (!drive.device) return;
+      try {
+        await invoke("eject_drive", { device: drive.device });
+      } catch (err) {
+        fs.setError(`Failed to eject: ${err}`);
+      }
+    },
+    [fs],
+  );
   const handleDelete = useCallback(
     (useTrash = true) => {
       if (fs.selectedItems.size === 0) return;
@@ -386,9 +416,7 @@ const App: React.FC = () => {
 
   const getItemActions = useCallback(
     (entry: FileEntry): ContextMenuAction[] => {
-      const isArchive = [".zip", ".7z", ".rar", ".tar", ".gz"].some((ext) =>
-        entry.name.toLowerCase().endsWith(ext),
-      );
+      const isArchive = isArchiveName(entry.name) && !entry.in_archive;
       const isInRecycleBin = fs.currentPath === "Recycle Bin";
 
       return [
@@ -474,7 +502,7 @@ const App: React.FC = () => {
               {
                 label: "Extract Here",
                 action: async () => {
-                  const outputDir = entry.path.replace(/\.[^.]+$/, "");
+                  const outputDir = entry.path.replace(/(\.tar)?\.[^.]+$/, "");
                   try {
                     await invoke("extract_archive", {
                       archivePath: entry.path,
@@ -486,39 +514,55 @@ const App: React.FC = () => {
                     });
                     fs.refresh();
                   } catch (err) {
-                    fs.setError(`Failed to extract: ${err}`);
+                    const { message } = err as ArchiveError;
+                    fs.setError(`Failed to extract: ${message ?? err}`);
                   }
                 },
               } as ContextMenuAction,
             ]
           : []),
         { label: "", action: () => {}, separator: true },
-        {
-          label: "Compress to ZIP",
-          action: async () => {
-            const selectedPaths =
-              fs.selectedItems.size > 0
-                ? Array.from(fs.selectedItems)
-                : [entry.path];
-            const outputPath =
-              selectedPaths.length === 1
-                ? `${selectedPaths[0]}.zip`
-                : `${fs.currentPath}\\Archive.zip`;
-            try {
-              await invoke("compress_items", {
-                paths: selectedPaths,
-                outputPath,
-              });
-              await invoke("send_notification", {
-                title: "Compression Complete",
-                body: `Created ${outputPath.split("\\").pop()}`,
-              });
-              fs.refresh();
-            } catch (err) {
-              fs.setError(`Failed to compress: ${err}`);
-            }
-          },
-        },
+        ...COMPRESS_FORMATS.map(
+          ({ label, format, extension }): ContextMenuAction => ({
+            label: `Compress to ${label}`,
+            action: async () => {
+              const selectedPaths =
+                fs.selectedItems.size > 0
+                  ? Array.from(fs.selectedItems)
+                  : [entry.path];
+              const sep = fs.osType === "windows" ? "\\" : "/";
+              const outputPath =
+                selectedPaths.length === 1
+                  ? `${selectedPaths[0]}${extension}`
+                  : `${fs.currentPath}${sep}Archive${extension}`;
+              const options: CompressOptions = {
+                method: settings.compressionMethod,
+                level: compressionLevelFor(
+                  format,
+                  settings.compressionMethod,
+                  settings.compressionLevel,
+                ),
+                preserve_permissions: settings.archivePermissions,
+                include_hidden: settings.archiveHidden,
+              };
+              try {
+                await invoke("compress_items", {
+                  paths: selectedPaths,
+                  outputPath,
+                  format,
+                  options,
+                });
+                await invoke("send_notification", {
+                  title: "Compression Complete",
+                  body: `Created ${outputPath.split(sep).pop()}`,
+                });
+                fs.refresh();
+              } catch (err) {
+                fs.setError(`Failed to compress: ${err}`);
+              }
+            },
+          }),
+        ),
         { label: "", action: () => {}, separator: true },
         {
           label: "Cut",
@@ -795,7 +839,7 @@ const App: React.FC = () => {
           setPropertiesDialog({ visible: true, path: fs.currentPath }),
       },
     ],
-    [fs, isBookmarked, bookmarks, addBookmark, removeBookmark],
+    [fs, isBookmarked, bookmarks, addBookmark, removeBookmark, settings],
   );
 
   // Keyboard shortcuts
@@ -981,6 +1025,7 @@ const App: React.FC = () => {
           onNavigate={fs.navigateTo}
           onRemoveBookmark={removeBookmark}
           onOpenRecycleBin={handleOpenRecycleBin}
+          onEjectDrive={handleEjectDrive}
           dragDrop={{
             dropTarget: dragDrop.dropTarget,
             onDragOver: dragDrop.handleDragOverFolder,
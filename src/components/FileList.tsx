@@ -281,7 +281,7 @@ const FileList: React.FC<FileListProps> = ({
                   <div className="details-col name-col">
                     <FileIcon entry={entry} size={20} />
                     <span className="file-name" title={entry.name}>
-                      {entry.name}
+                      {entry.display_name ?? entry.name}
                     </span>
                   </div>
                   <div className="details-col date-col">
@@ -358,7 +358,9 @@ const FileList: React.FC<FileListProps> = ({
                 <FileIcon entry={entry} size={viewMode === "grid" ? 48 : 24} />
               </div>
               <div className="file-card-info">
-                <span className="file-name">{entry.name}</span>
+                <span className="file-name">
+                  {entry.display_name ?? entry.name}
+                </span>
                 {viewMode === "list" && !entry.is_dir && (
                   <span className="file-size">
                     {formatFileSize(entry.size)}
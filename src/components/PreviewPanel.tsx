@@ -1,12 +1,20 @@
 import React, { useEffect, useState, useMemo, useCallback } from "react";
 import { invoke } from "@tauri-apps/api/core";
 import { convertFileSrc } from "@tauri-apps/api/core";
-import type { FileEntry } from "../types";
+import type { ArchiveError, FileEntry, FileTypeInfo } from "../types";
 import { formatFileSize, formatDate } from "../utils/formatters";
-import { classifyFile } from "../utils/fileClassification";
+import {
+  classifyFile,
+  previewKindFromDetected,
+} from "../utils/fileClassification";
 import FileIcon from "./FileIcon";
 import { PreviewContent } from "./preview/PreviewContent";
 
+// Archive members have no file to load; the backend reads them out
+function readArchiveEntry(path: string, maxBytes?: number) {
+  return invoke<ArrayBuffer>("read_archive_entry", { path, maxBytes });
+}
+
 interface PreviewPanelProps {
   file: FileEntry | null;
   onClose: () => void;
@@ -16,11 +24,34 @@ const PreviewPanel: React.FC<PreviewPanelProps> = ({ file, onClose }) => {
   const [content, setContent] = useState<string | null>(null);
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
+  const [detected, setDetected] = useState<FileTypeInfo | null>(null);
 
-  const kind = useMemo(
-    () => (file ? classifyFile(file.extension) : "unsupported"),
-    [file],
-  );
+  // Sniff the content so extensionless or misnamed files get the right previewer
+  useEffect(() => {
+    setDetected(null);
+    if (!file || file.is_dir || file.in_archive) return;
+    let cancelled = false;
+    invoke<FileTypeInfo[]>("detect_file_types", { paths: [file.path] })
+      .then(([info]) => {
+        if (!cancelled && info) setDetected(info);
+      })
+      .catch(() => {});
+    return () => {
+      cancelled = true;
+    };
+  }, [file]);
+
+  const kind = useMemo(() => {
+    if (!file) return "unsupported";
+    const byExtension = classifyFile(file.extension);
+    // Guesses from the name alone are coarser than our own extension table
+    const sniffed =
+      detected?.path === file.path &&
+      (detected.source === "content" || byExtension === "unsupported")
+        ? previewKindFromDetected(detected.kind)
+        : null;
+    return sniffed ?? byExtension;
+  }, [file, detected]);
 
   const loadContent = useCallback(async () => {
     if (!file) return;
@@ -36,10 +67,14 @@ const PreviewPanel: React.FC<PreviewPanelProps> = ({ file, onClose }) => {
         case "csv":
         case "json":
         case "svg": {
-          const text: string = await invoke("read_file_text", {
-            path: file.path,
-            maxBytes: 2 * 1024 * 1024,
-          });
+          const text: string = file.in_archive
+            ? new TextDecoder().decode(
+                await readArchiveEntry(file.path, 2 * 1024 * 1024),
+              )
+            : await invoke("read_file_text", {
+                path: file.path,
+                maxBytes: 2 * 1024 * 1024,
+              });
           setContent(text);
           break;
         }
@@ -47,13 +82,19 @@ const PreviewPanel: React.FC<PreviewPanelProps> = ({ file, onClose }) => {
         case "audio":
         case "video":
         case "pdf":
-          setContent(convertFileSrc(file.path));
+          if (file.in_archive) {
+            const type = kind === "pdf" ? "application/pdf" : "";
+            const bytes = await readArchiveEntry(file.path);
+            setContent(URL.createObjectURL(new Blob([bytes], { type })));
+          } else {
+            setContent(convertFileSrc(file.path));
+          }
           break;
         default:
           setContent(null);
       }
     } catch (err) {
-      setError(String(err));
+      setError((err as ArchiveError)?.message ?? String(err));
     } finally {
       setLoading(false);
     }
@@ -63,6 +104,13 @@ const PreviewPanel: React.FC<PreviewPanelProps> = ({ file, onClose }) => {
     loadContent();
   }, [loadContent]);
 
+  // Release object URLs made for archive members
+  useEffect(() => {
+    return () => {
+      if (content?.startsWith("blob:")) URL.revokeObjectURL(content);
+    };
+  }, [content]);
+
   if (!file) return null;
 
   return (
@@ -70,7 +118,9 @@ const PreviewPanel: React.FC<PreviewPanelProps> = ({ file, onClose }) => {
       <div className="preview-header">
         <div className="preview-title-row">
           <FileIcon entry={file} size={24} />
-          <span className="preview-filename">{file.name}</span>
+          <span className="preview-filename">
+            {file.display_name ?? file.name}
+          </span>
         </div>
         <button className="preview-close" onClick={onClose} title="Close preview">
           &#x2715;
@@ -1,7 +1,17 @@
 import { invoke } from "@tauri-apps/api/core";
-import React, { useCallback, useEffect, useState } from "react";
+import { listen } from "@tauri-apps/api/event";
+import React, { useCallback, useEffect, useRef, useState } from "react";
 import { VscClose, VscFile, VscFolder } from "react-icons/vsc";
-import type { FileProperties } from "../types";
+import type {
+  Acl,
+  AclEntry,
+  DirSizeProgress,
+  FileHashes,
+  FileProperties,
+  HashProgress,
+  ItemResult,
+  Xattr,
+} from "../types";
 import { formatFileSize } from "../utils/formatters";
 
 interface PropertiesDialogProps {
@@ -44,6 +54,40 @@ const PropertiesDialog: React.FC<PropertiesDialogProps> = ({
     }
   }, [visible, path, loadProperties]);
 
+  // Folder sizes are computed in the background and fill in as they grow
+  const sizePending = !!props?.size_pending;
+  useEffect(() => {
+    if (!sizePending) return;
+    let jobId: number | null = null;
+    let closed = false;
+
+    const unlisten = listen<DirSizeProgress>("dir-size-progress", (event) => {
+      const { id, path: jobPath, size, done } = event.payload;
+      if (jobPath !== path || (jobId !== null && id !== jobId)) return;
+      setProps((prev) =>
+        prev && {
+          ...prev,
+          size: size.apparent,
+          size_on_disk: size.on_disk,
+          size_pending: !done,
+        },
+      );
+    });
+
+    invoke<number>("start_dir_size", { path })
+      .then((id) => {
+        jobId = id;
+        if (closed) invoke("cancel_dir_size", { id }).catch(() => {});
+      })
+      .catch((err) => console.error("Size calculation failed:", err));
+
+    return () => {
+      closed = true;
+      unlisten.then((fn) => fn());
+      if (jobId !== null) invoke("cancel_dir_size", { id: jobId }).catch(() => {});
+    };
+  }, [sizePending, path]);
+
   if (!visible) return null;
 
   return (
@@ -84,8 +128,16 @@ const PropertiesDialog: React.FC<PropertiesDialogProps> = ({
                 <PropertyRow
                   label="Size"
                   value={
-                    props.size > 0
+                    (props.size > 0
                       ? `${formatFileSize(props.size)} (${props.size.toLocaleString()} bytes)`
+                      : "0 bytes") + (props.size_pending ? " (calculating...)" : "")
+                  }
+                />
+                <PropertyRow
+                  label="Size on disk"
+                  value={
+                    props.size_on_disk > 0
+                      ? `${formatFileSize(props.size_on_disk)} (${props.size_on_disk.toLocaleString()} bytes)`
                       : "0 bytes"
                   }
                 />
@@ -118,6 +170,24 @@ const PropertiesDialog: React.FC<PropertiesDialogProps> = ({
                   </span>
                 </div>
               </div>
+
+              {props.details.permissions && (
+                <PermissionsSection
+                  path={path}
+                  props={props}
+                  onChanged={loadProperties}
+                />
+              )}
+
+              {props.acl && <AclSection acl={props.acl} />}
+
+              {!props.is_dir && <HashesSection path={path} />}
+
+              <XattrsSection
+                path={path}
+                xattrs={props.xattrs}
+                onChanged={loadProperties}
+              />
             </>
           )}
         </div>
@@ -126,6 +196,306 @@ const PropertiesDialog: React.FC<PropertiesDialogProps> = ({
   );
 };
 
+// Unix mode and ownership, with an inline chmod/chown form
+function PermissionsSection({
+  path,
+  props,
+  onChanged,
+}: {
+  path: string;
+  props: FileProperties;
+  onChanged: () => void;
+}) {
+  const { details } = props;
+  const octal = ((details.mode ?? 0) & 0o7777).toString(8).padStart(4, "0");
+  const [mode, setMode] = useState("");
+  const [owner, setOwner] = useState(details.owner ?? "");
+  const [group, setGroup] = useState(details.group ?? "");
+  const [recursive, setRecursive] = useState(false);
+  const [status, setStatus] = useState<string | null>(null);
+
+  const apply = async () => {
+    setStatus(null);
+    try {
+      const results: ItemResult[] = [];
+      if (mode.trim()) {
+        results.push(
+          ...(await invoke<ItemResult[]>("set_permissions", {
+            paths: [path],
+            options: { mode: mode.trim(), recursive },
+          })),
+        );
+      }
+      const user = owner !== (details.owner ?? "") ? owner : undefined;
+      const grp = group !== (details.group ?? "") ? group : undefined;
+      if (user || grp) {
+        results.push(
+          ...(await invoke<ItemResult[]>("set_owner", {
+            paths: [path],
+            options: { user, group: grp, recursive },
+          })),
+        );
+      }
+      const failed = results.filter((r) => !r.ok);
+      setStatus(
+        failed.length === 0
+          ? null
+          : `${failed.length} of ${results.length} failed: ${failed[0].error}`,
+      );
+      setMode("");
+      onChanged();
+    } catch (err) {
+      setStatus(String(err));
+    }
+  };
+
+  return (
+    <div className="properties-section">
+      <h4>Permissions</h4>
+      <PropertyRow label="Mode" value={`${details.permissions} (${octal})`} />
+      <PropertyRow
+        label="Owner"
+        value={`${details.owner ?? details.uid} : ${details.group ?? details.gid}`}
+      />
+      <div className="property-row">
+        <span className="property-label">Change</span>
+        <input
+          className="dialog-input"
+          value={mode}
+          placeholder="755 or u+x,go-w"
+          onChange={(e) => setMode(e.target.value)}
+        />
+      </div>
+      <div className="property-row">
+        <span className="property-label">Owner</span>
+        <input
+          className="dialog-input"
+          value={owner}
+          onChange={(e) => setOwner(e.target.value)}
+        />
+        <input
+          className="dialog-input"
+          value={group}
+          onChange={(e) => setGroup(e.target.value)}
+        />
+      </div>
+      <div className="property-row">
+        {props.is_dir && (
+          <label>
+            <input
+              type="checkbox"
+              checked={recursive}
+              onChange={(e) => setRecursive(e.target.checked)}
+            />{" "}
+            Apply to contents
+          </label>
+        )}
+        <button className="dialog-btn primary" onClick={apply}>
+          Apply
+        </button>
+      </div>
+      {status && <div className="properties-error">{status}</div>}
+    </div>
+  );
+}
+
+// Checksums on demand, with a field to compare against a published hash
+function HashesSection({ path }: { path: string }) {
+  const [result, setResult] = useState<FileHashes | null>(null);
+  const [progress, setProgress] = useState<number | null>(null);
+  const [expected, setExpected] = useState("");
+  const jobRef = useRef<number | null>(null);
+
+  useEffect(() => {
+    const unlisten = listen<HashProgress>("hash-progress", (event) => {
+      const p = event.payload;
+      if (p.id !== jobRef.current) return;
+      if (p.result) setResult(p.result);
+      if (p.done) {
+        jobRef.current = null;
+        setProgress(null);
+      } else if (p.bytes_total > 0) {
+        setProgress(Math.round((p.bytes_done / p.bytes_total) * 100));
+      }
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+      if (jobRef.current !== null) {
+        invoke("cancel_hashes", { id: jobRef.current }).catch(() => {});
+      }
+    };
+  }, [path]);
+
+  const start = async () => {
+    setResult(null);
+    setProgress(0);
+    try {
+      jobRef.current = await invoke<number>("compute_hashes", {
+        paths: [path],
+        algorithms: ["md5", "sha1", "sha256", "sha512", "blake3", "crc32"],
+      });
+    } catch (err) {
+      setProgress(null);
+      console.error("Hashing failed:", err);
+    }
+  };
+
+  const want = expected.trim().toLowerCase();
+  const matched = result
+    ? Object.entries(result.hashes).find(([, h]) => h === want)
+    : undefined;
+
+  return (
+    <div className="properties-section">
+      <h4>Checksums</h4>
+      {result?.error && <div className="properties-error">{result.error}</div>}
+      {result &&
+        Object.entries(result.hashes).map(([algo, hash]) => (
+          <PropertyRow key={algo} label={algo.toUpperCase()} value={hash} />
+        ))}
+      <div className="property-row">
+        <input
+          className="dialog-input"
+          value={expected}
+          placeholder="Paste expected checksum"
+          onChange={(e) => setExpected(e.target.value)}
+        />
+        {progress === null ? (
+          <button className="dialog-btn primary" onClick={start}>
+            Calculate
+          </button>
+        ) : (
+          <span className="property-value">{progress}%</span>
+        )}
+      </div>
+      {result && want && (
+        <PropertyRow
+          label="Verify"
+          value={matched ? `Matches ${matched[0].toUpperCase()}` : "No match"}
+        />
+      )}
+    </div>
+  );
+}
+
+function aclLabel(entry: AclEntry): string {
+  const who = entry.name ?? entry.id;
+  switch (entry.tag) {
+    case "user_obj":
+      return "Owner";
+    case "user":
+      return `User ${who}`;
+    case "group_obj":
+      return "Group";
+    case "group":
+      return `Group ${who}`;
+    case "mask":
+      return "Mask";
+    case "other":
+      return "Others";
+  }
+}
+
+function aclPerms(entry: AclEntry): string {
+  const { read, write, execute } = entry.effective;
+  const granted = `${read ? "r" : "-"}${write ? "w" : "-"}${execute ? "x" : "-"}`;
+  const { perms } = entry;
+  const masked =
+    perms.read !== read || perms.write !== write || perms.execute !== execute;
+  return masked ? `${granted} (masked)` : granted;
+}
+
+// Who has access, with the mask already applied
+function AclSection({ acl }: { acl: Acl }) {
+  if (!acl.extended && acl.default.length === 0) return null;
+  return (
+    <div className="properties-section">
+      <h4>Access Control</h4>
+      {acl.access.map((e, i) => (
+        <PropertyRow key={`a${i}`} label={aclLabel(e)} value={aclPerms(e)} />
+      ))}
+      {acl.default.map((e, i) => (
+        <PropertyRow
+          key={`d${i}`}
+          label={`Default: ${aclLabel(e)}`}
+          value={aclPerms(e)}
+        />
+      ))}
+    </div>
+  );
+}
+
+// Extended attributes; only user.* ones can be edited
+function XattrsSection({
+  path,
+  xattrs,
+  onChanged,
+}: {
+  path: string;
+  xattrs: Xattr[];
+  onChanged: () => void;
+}) {
+  const [name, setName] = useState("user.");
+  const [value, setValue] = useState("");
+  const [status, setStatus] = useState<string | null>(null);
+
+  const run = async (cmd: string, args: Record<string, string>) => {
+    setStatus(null);
+    try {
+      await invoke(cmd, { path, ...args });
+      onChanged();
+    } catch (err) {
+      setStatus(String(err));
+    }
+  };
+
+  return (
+    <div className="properties-section">
+      <h4>Extended Attributes</h4>
+      {xattrs.length === 0 && <PropertyRow label="" value="None" />}
+      {xattrs.map((x) => (
+        <div className="property-row" key={x.name}>
+          <span className="property-label" title={x.name}>
+            {x.name}
+          </span>
+          <span className="property-value" title={x.text ?? x.value}>
+            {x.text ?? `(binary, ${x.size} bytes)`}
+          </span>
+          {x.writable && (
+            <button
+              className="preview-close"
+              title="Remove"
+              onClick={() => run("remove_xattr", { name: x.name })}
+            >
+              <VscClose />
+            </button>
+          )}
+        </div>
+      ))}
+      <div className="property-row">
+        <input
+          className="dialog-input"
+          value={name}
+          onChange={(e) => setName(e.target.value)}
+        />
+        <input
+          className="dialog-input"
+          value={value}
+          placeholder="Value"
+          onChange={(e) => setValue(e.target.value)}
+        />
+        <button
+          className="dialog-btn primary"
+          onClick={() => run("set_xattr", { name, value })}
+        >
+          Set
+        </button>
+      </div>
+      {status && <div className="properties-error">{status}</div>}
+    </div>
+  );
+}
+
 function PropertyRow({ label, value }: { label: string; value: string }) {
   return (
     <div className="property-row">
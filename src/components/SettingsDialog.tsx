@@ -1,6 +1,16 @@
 import React, { useState, useCallback, useEffect, useRef } from "react";
-import type { AppSettings, SortField, SortDirection, ViewMode } from "../types";
+import type {
+  AppSettings,
+  CompressionMethod,
+  SortField,
+  SortDirection,
+  ViewMode,
+} from "../types";
 import { DEFAULT_SETTINGS } from "../types";
+import {
+  clampCompressionLevel,
+  compressionLevelRange,
+} from "../utils/compression";
 import { VscSettingsGear, VscClose, VscRefresh } from "react-icons/vsc";
 
 interface SettingsDialogProps {
@@ -27,6 +37,22 @@ const VIEW_MODES: { value: ViewMode; label: string }[] = [
   { value: "details", label: "Details" },
 ];
 
+const LOW_SPACE_LIMITS: { value: number; label: string }[] = [
+  { value: 256, label: "256 MB" },
+  { value: 512, label: "512 MB" },
+  { value: 1024, label: "1 GB" },
+  { value: 5 * 1024, label: "5 GB" },
+  { value: 10 * 1024, label: "10 GB" },
+  { value: 50 * 1024, label: "50 GB" },
+];
+
+const COMPRESSION_METHODS: { value: CompressionMethod; label: string }[] = [
+  { value: "store", label: "Store" },
+  { value: "deflate", label: "Deflate" },
+  { value: "bzip2", label: "Bzip2" },
+  { value: "zstd", label: "Zstandard" },
+];
+
 const SettingsDialog: React.FC<SettingsDialogProps> = ({
   visible,
   settings,
@@ -40,6 +66,8 @@ const SettingsDialog: React.FC<SettingsDialogProps> = ({
 
   if (!visible) return null;
 
+  const levelRange = compressionLevelRange(settings.compressionMethod);
+
   return (
     <div className="dialog-overlay" onClick={onClose}>
       <div className="settings-dialog" onClick={(e) => e.stopPropagation()}>
@@ -142,6 +170,48 @@ const SettingsDialog: React.FC<SettingsDialogProps> = ({
                 checked={settings.showPreviewOnSelect}
                 onChange={(v) => onUpdate("showPreviewOnSelect", v)}
               />
+
+              <SettingsToggle
+                label="Low Disk Space Alerts"
+                checked={settings.lowSpaceAlerts}
+                onChange={(v) => onUpdate("lowSpaceAlerts", v)}
+              />
+
+              {settings.lowSpaceAlerts && (
+                <SettingsRow
+                  label={`Alert Below: ${settings.lowSpacePercent}% Free`}
+                >
+                  <input
+                    type="range"
+                    className="settings-range"
+                    min={1}
+                    max={50}
+                    step={1}
+                    value={settings.lowSpacePercent}
+                    onChange={(e) =>
+                      onUpdate("lowSpacePercent", Number(e.target.value))
+                    }
+                  />
+                </SettingsRow>
+              )}
+
+              {settings.lowSpaceAlerts && (
+                <SettingsRow label="And Below">
+                  <select
+                    className="settings-select"
+                    value={settings.lowSpaceMinFreeMb}
+                    onChange={(e) =>
+                      onUpdate("lowSpaceMinFreeMb", Number(e.target.value))
+                    }
+                  >
+                    {LOW_SPACE_LIMITS.map((l) => (
+                      <option key={l.value} value={l.value}>
+                        {l.label} Free
+                      </option>
+                    ))}
+                  </select>
+                </SettingsRow>
+              )}
             </div>
           )}
 
@@ -210,6 +280,67 @@ const SettingsDialog: React.FC<SettingsDialogProps> = ({
                 />
               </SettingsRow>
 
+              <SettingsRow label="Zip Compression">
+                <select
+                  className="settings-select"
+                  value={settings.compressionMethod}
+                  onChange={(e) => {
+                    const method = e.target.value as CompressionMethod;
+                    onUpdate("compressionMethod", method);
+                    onUpdate(
+                      "compressionLevel",
+                      clampCompressionLevel(method, settings.compressionLevel),
+                    );
+                  }}
+                >
+                  {COMPRESSION_METHODS.map((m) => (
+                    <option key={m.value} value={m.value}>
+                      {m.label}
+                    </option>
+                  ))}
+                </select>
+              </SettingsRow>
+
+              {levelRange && (
+                <SettingsToggle
+                  label="Default Compression Level"
+                  checked={settings.compressionLevel === null}
+                  onChange={(v) =>
+                    onUpdate("compressionLevel", v ? null : levelRange[0])
+                  }
+                />
+              )}
+
+              {levelRange && settings.compressionLevel !== null && (
+                <SettingsRow
+                  label={`Compression Level: ${settings.compressionLevel}`}
+                >
+                  <input
+                    type="range"
+                    className="settings-range"
+                    min={levelRange[0]}
+                    max={levelRange[1]}
+                    step={1}
+                    value={settings.compressionLevel}
+                    onChange={(e) =>
+                      onUpdate("compressionLevel", Number(e.target.value))
+                    }
+                  />
+                </SettingsRow>
+              )}
+
+              <SettingsToggle
+                label="Keep Permissions in Archives"
+                checked={settings.archivePermissions}
+                onChange={(v) => onUpdate("archivePermissions", v)}
+              />
+
+              <SettingsToggle
+                label="Include Hidden Files in Archives"
+                checked={settings.archiveHidden}
+                onChange={(v) => onUpdate("archiveHidden", v)}
+              />
+
               <div className="settings-reset-row">
                 <button className="dialog-btn cancel" onClick={onReset}>
                   <VscRefresh style={{ marginRight: 6 }} />
@@ -39,9 +39,14 @@ interface SidebarProps {
   onNavigate: (path: string) => void;
   onRemoveBookmark: (id: string) => void;
   onOpenRecycleBin?: () => void;
+  onEjectDrive?: (drive: DriveItem) => void;
   dragDrop?: SidebarDragDrop;
 }
 
+// Volumes the desktop automounted, which are the ones safe to eject
+const isRemovableMount = (drive: DriveItem) =>
+  !!drive.device && /^\/(run\/)?media\//.test(drive.path);
+
 const Sidebar: React.FC<SidebarProps> = ({
   quickAccess,
   currentPath,
@@ -50,6 +55,7 @@ const Sidebar: React.FC<SidebarProps> = ({
   onNavigate,
   onRemoveBookmark,
   onOpenRecycleBin,
+  onEjectDrive,
   dragDrop,
 }) => {
   return (
@@ -148,6 +154,18 @@ const Sidebar: React.FC<SidebarProps> = ({
                       </div>
                     )}
                 </div>
+                {onEjectDrive && isRemovableMount(drive) && (
+                  <button
+                    className="sidebar-remove-btn"
+                    onClick={(e) => {
+                      e.stopPropagation();
+                      onEjectDrive(drive);
+                    }}
+                    title="Safely remove"
+                  >
+                    <VscClose />
+                  </button>
+                )}
               </li>
             ))}
           </ul>
@@ -2,19 +2,45 @@ import { invoke } from "@tauri-apps/api/core";
 import { listen } from "@tauri-apps/api/event";
 import { useCallback, useEffect, useRef, useState } from "react";
 import type {
+  ArchiveError,
   ClipboardState,
   DirContents,
   DriveItem,
+  DrivesChangedEvent,
   FileEntry,
+  FsChange,
+  FsChangedEvent,
   OsType,
   QuickAccessItem,
   SortConfig,
   SystemClipboardFiles,
+  SystemDrive,
 } from "../types";
 import { debugLogger } from "../utils/debugLogger";
+import { isArchiveName, isArchivePath } from "../utils/fileClassification";
+
+function applyFsChanges(
+  entries: FileEntry[],
+  changes: FsChange[],
+  showHidden: boolean,
+): FileEntry[] {
+  const byPath = new Map(entries.map((e) => [e.path, e]));
+  for (const change of changes) {
+    if (change.kind === "removed") {
+      byPath.delete(change.path);
+      continue;
+    }
+    if (change.kind === "renamed") byPath.delete(change.from);
+    if (showHidden || !change.entry.is_hidden) {
+      byPath.set(change.entry.path, change.entry);
+    }
+  }
+  return Array.from(byPath.values());
+}
 
 export function useFileSystem() {
   const [currentPath, setCurrentPath] = useState<string>("");
+  const [inArchive, setInArchive] = useState(false);
   const [entries, setEntries] = useState<FileEntry[]>([]);
   const [loading, setLoading] = useState(false);
   const [error, setError] = useState<string | null>(null);
@@ -53,19 +79,19 @@ export function useFileSystem() {
 
   const loadDrives = useCallback(async () => {
     try {
-      const driveList: [string, string][] = await invoke("get_system_drives");
+      const driveList: SystemDrive[] = await invoke("get_system_drives");
       const drivesWithSpace = await Promise.all(
-        driveList.map(async ([label, path]) => {
+        driveList.map(async (drive) => {
           try {
             const space = await invoke<{
               total_bytes: number;
               available_bytes: number;
               used_bytes: number;
               percent_used: number;
-            }>("get_drive_space", { path });
-            return { label, path, ...space };
+            }>("get_drive_space", { path: drive.path });
+            return { ...drive, ...space };
           } catch {
-            return { label, path };
+            return drive;
           }
         }),
       );
@@ -154,6 +180,7 @@ export function useFileSystem() {
 
             setEntries(entries);
             setCurrentPath("Recycle Bin");
+            setInArchive(false);
             debugLogger.info(
               "navigation",
               `[RECYCLE BIN] Successfully loaded recycle bin: ${entries.length} items`,
@@ -174,12 +201,14 @@ export function useFileSystem() {
             debugLogger.error("navigation", error, err);
           }
         } else {
-          const result: DirContents = await invoke("list_directory", {
-            path,
-            showHidden: showHidden,
-          });
+          // Paths running through an archive are listed from inside it
+          const result: DirContents = await invoke(
+            isArchivePath(path) ? "list_archive" : "list_directory",
+            { path, showHidden: showHidden },
+          );
           setEntries(result.entries);
           setCurrentPath(result.path);
+          setInArchive(result.in_archive);
           debugLogger.info("navigation", `Loaded directory: ${result.path}`, {
             itemCount: result.entries.length,
           });
@@ -194,7 +223,8 @@ export function useFileSystem() {
           }
         }
       } catch (err) {
-        const error = String(err);
+        // list_archive rejects with an ArchiveError object
+        const error = (err as ArchiveError)?.message ?? String(err);
         setError(error);
         debugLogger.error("navigation", "Navigation failed", err);
       } finally {
@@ -270,33 +300,37 @@ export function useFileSystem() {
   }, [currentPath, navigateTo]);
 
   // Watch current directory for changes
-  const watchTimerRef = useRef<ReturnType<typeof setTimeout> | null>(null);
-
   useEffect(() => {
-    if (!currentPath) return;
-
-    // Start watching
-    invoke("watch_directory", { path: currentPath }).catch((err) =>
-      console.warn("Failed to watch directory:", err),
-    );
-
-    // Listen for fs-changed events (debounced)
-    const unlisten = listen<string>("fs-changed", () => {
-      // Debounce: only refresh after 300ms of no events
-      if (watchTimerRef.current) clearTimeout(watchTimerRef.current);
-      watchTimerRef.current = setTimeout(() => {
-        if (currentPath && !isNavigating.current) {
-          navigateTo(currentPath, false);
-        }
-      }, 300);
+    // Archive contents never change underneath us; a real folder named like
+    // an archive is still watched
+    if (!currentPath || inArchive) return;
+
+    // Start watching; the subscription ID is needed to stop again
+    const subscription = invoke<number>("watch_directory", {
+      path: currentPath,
+    }).catch((err) => {
+      console.warn("Failed to watch directory:", err);
+      return null;
+    });
+
+    // Changes arrive already debounced and typed; patch entries in place
+    const unlisten = listen<FsChangedEvent>("fs-changed", (event) => {
+      const { path, recursive, changes, rescan } = event.payload;
+      if (path !== currentPath || recursive || isNavigating.current) return;
+      if (rescan) {
+        navigateTo(currentPath, false);
+        return;
+      }
+      setEntries((prev) => applyFsChanges(prev, changes, showHidden));
     });
 
     return () => {
       unlisten.then((fn) => fn());
-      if (watchTimerRef.current) clearTimeout(watchTimerRef.current);
-      invoke("unwatch_directory").catch(() => {});
+      subscription.then((id) => {
+        if (id !== null) invoke("unwatch_directory", { id }).catch(() => {});
+      });
     };
-  }, [currentPath, navigateTo]);
+  }, [currentPath, inArchive, navigateTo, showHidden]);
 
   const toggleHidden = useCallback(() => {
     setShowHidden((prev) => !prev);
@@ -310,8 +344,11 @@ export function useFileSystem() {
 
   const openItem = useCallback(
     async (item: FileEntry) => {
-      if (item.is_dir) {
+      if (item.is_dir || isArchiveName(item.name)) {
+        // Archives open like folders
         navigateTo(item.path);
+      } else if (item.in_archive) {
+        setError(`Extract the archive to open ${item.name}`);
       } else {
         try {
           await invoke("open_file", { path: item.path });
@@ -542,6 +579,16 @@ export function useFileSystem() {
 
   const displayEntries = sortEntries(searchResults ?? entries);
 
+  // Volumes mounted, unmounted or plugged in elsewhere
+  useEffect(() => {
+    const unlisten = listen<DrivesChangedEvent>("drives-changed", () => {
+      loadDrives();
+    });
+    return () => {
+      unlisten.then((fn) => fn());
+    };
+  }, [loadDrives]);
+
   // Initialize
   useEffect(() => {
     (async () => {
@@ -1,5 +1,6 @@
+import { invoke } from "@tauri-apps/api/core";
 import { useCallback, useEffect, useState } from "react";
-import type { AppSettings } from "../types";
+import type { AppSettings, SpaceAlertOptions } from "../types";
 import { DEFAULT_SETTINGS } from "../types";
 
 const STORAGE_KEY = "file-explorer-settings";
@@ -32,6 +33,21 @@ export function useSettings() {
     document.documentElement.setAttribute("data-theme", settings.theme);
   }, [settings.fontSize, settings.theme]);
 
+  useEffect(() => {
+    const options: SpaceAlertOptions = {
+      enabled: settings.lowSpaceAlerts,
+      min_free_percent: settings.lowSpacePercent,
+      min_free_bytes: settings.lowSpaceMinFreeMb * 1024 * 1024,
+    };
+    invoke("set_space_alerts", { options }).catch((err) =>
+      console.warn("Failed to set space alerts:", err),
+    );
+  }, [
+    settings.lowSpaceAlerts,
+    settings.lowSpacePercent,
+    settings.lowSpaceMinFreeMb,
+  ]);
+
   const updateSetting = useCallback(
     <K extends keyof AppSettings>(key: K, value: AppSettings[K]) => {
       setSettings((prev) => ({ ...prev, [key]: value }));
@@ -1,5 +1,7 @@
 export interface FileEntry {
+  // name/path escape non-UTF-8 bytes reversibly; pass them back unchanged
   name: string;
+  display_name?: string;
   path: string;
   is_dir: boolean;
   is_hidden: boolean;
@@ -7,12 +9,83 @@ export interface FileEntry {
   modified: string;
   extension: string;
   is_symlink: boolean;
+  modified_at?: Timestamp | null;
+  details?: EntryDetails;
+  // Member of an archive (list_archive); read it with read_archive_entry
+  in_archive?: boolean;
+}
+
+// Seconds + nanoseconds since the Unix epoch (UTC)
+export interface Timestamp {
+  secs: number;
+  nanos: number;
+}
+
+// Only present when requested (ListOptions.detailed / get_file_details)
+export interface EntryDetails {
+  mode: number | null;
+  permissions: string | null;
+  uid: number | null;
+  gid: number | null;
+  owner: string | null;
+  group: string | null;
+  inode: number | null;
+  device: number | null;
+  nlink: number | null;
+  link_target: string | null;
+  is_broken_link: boolean;
+  created: Timestamp | null;
+  modified: Timestamp | null;
+  accessed: Timestamp | null;
 }
 
 export interface DirContents {
   path: string;
   entries: FileEntry[];
   parent: string | null;
+  groups: EntryGroup[] | null;
+  in_archive: boolean;
+}
+
+// Payload of the "fs-changed" event
+export type FsChange =
+  | { kind: "created"; entry: FileEntry }
+  | { kind: "modified"; entry: FileEntry }
+  | { kind: "removed"; path: string }
+  | { kind: "renamed"; from: string; entry: FileEntry };
+
+export interface FsChangedEvent {
+  path: string;
+  // Set by recursive watches; changes may be anywhere below path
+  recursive: boolean;
+  changes: FsChange[];
+  rescan: boolean;
+}
+
+// Options for watch_directory; "auto" polls directories on network mounts
+export interface WatchOptions {
+  recursive?: boolean;
+  backend?: "auto" | "native" | "poll";
+  poll_interval_ms?: number;
+}
+
+// Server-side listing options for list_directory
+export interface EntryGroup {
+  label: string;
+  start: number;
+  count: number;
+}
+
+export interface ListOptions {
+  sort_by?: SortField | "type";
+  direction?: SortDirection;
+  folders_first?: boolean;
+  collation?: "simple" | "natural" | "locale";
+  locale?: string;
+  patterns?: string[];
+  kinds?: Exclude<FileFilterType, "all">[];
+  group_by?: GroupBy;
+  detailed?: boolean;
 }
 
 export interface QuickAccessItem {
@@ -23,7 +96,28 @@ export interface QuickAccessItem {
 
 export type OsType = "windows" | "linux" | "macos";
 
-export interface DriveItem {
+// get_system_drives; mount details are only filled in on Linux
+export interface SystemDrive {
+  label: string;
+  path: string;
+  device: string | null;
+  fs_type: string | null;
+  options: string[];
+  read_only: boolean;
+  noexec: boolean;
+  volume_label: string | null;
+  uuid: string | null;
+}
+
+// Payload of the "drives-changed" event: mount points, then removable device nodes
+export interface DrivesChangedEvent {
+  mounted: string[];
+  unmounted: string[];
+  added: string[];
+  removed: string[];
+}
+
+export interface DriveItem extends Partial<Omit<SystemDrive, "label" | "path">> {
   label: string;
   path: string;
   total_bytes?: number;
@@ -78,6 +172,12 @@ export interface ContextMenuAction {
   shortcut?: string;
 }
 
+// Options for calculate_dir_size
+export interface SizeOptions {
+  on_disk?: boolean;
+  one_file_system?: boolean;
+}
+
 // File Properties
 export interface FileProperties {
   name: string;
@@ -93,6 +193,93 @@ export interface FileProperties {
   is_symlink: boolean;
   extension: string;
   item_count: number | null;
+  // Folder size not computed yet; start_dir_size reports it via "dir-size-progress"
+  size_pending: boolean;
+  details: EntryDetails;
+  xattrs: Xattr[];
+  acl: Acl | null;
+}
+
+// POSIX ACLs; effective is perms with the mask applied
+export interface AclPerms {
+  read: boolean;
+  write: boolean;
+  execute: boolean;
+}
+
+export interface AclEntry {
+  tag: "user_obj" | "user" | "group_obj" | "group" | "mask" | "other";
+  id: number | null;
+  name: string | null;
+  perms: AclPerms;
+  effective: AclPerms;
+}
+
+export interface Acl {
+  access: AclEntry[];
+  default: AclEntry[];
+  extended: boolean;
+}
+
+// Extended attribute; value is base64, text is set when it is readable
+export interface Xattr {
+  name: string;
+  namespace: string;
+  value: string;
+  text: string | null;
+  size: number;
+  writable: boolean;
+}
+
+// Per-item outcome of set_permissions / set_owner / set_file_times
+export interface ItemResult {
+  path: string;
+  ok: boolean;
+  error: string | null;
+}
+
+export interface ChmodOptions {
+  // Octal ("755") or symbolic ("u+x,go-w")
+  mode?: string;
+  file_mode?: string;
+  dir_mode?: string;
+  recursive?: boolean;
+}
+
+export interface ChownOptions {
+  user?: string;
+  group?: string;
+  recursive?: boolean;
+}
+
+export interface TimesOptions {
+  modified?: Timestamp;
+  accessed?: Timestamp;
+  recursive?: boolean;
+}
+
+export interface DirSize {
+  apparent: number;
+  on_disk: number;
+  files: number;
+  dirs: number;
+}
+
+// Payload of the "dir-size-progress" event
+export interface DirSizeProgress {
+  id: number;
+  path: string;
+  size: DirSize;
+  done: boolean;
+  cancelled: boolean;
+}
+
+// Result of detect_file_types (content sniffing with extension fallback)
+export interface FileTypeInfo {
+  path: string;
+  mime: string;
+  kind: string;
+  source: "content" | "extension" | "generic";
 }
 
 // Bookmarks
@@ -123,6 +310,14 @@ export interface AppSettings {
   showPreviewOnSelect: boolean;
   sidebarWidth: number;
   terminalHeight: number;
+  lowSpaceAlerts: boolean;
+  lowSpacePercent: number;
+  lowSpaceMinFreeMb: number;
+  compressionMethod: CompressionMethod;
+  /** null uses the method's default level */
+  compressionLevel: number | null;
+  archivePermissions: boolean;
+  archiveHidden: boolean;
 }
 
 export const DEFAULT_SETTINGS: AppSettings = {
@@ -136,8 +331,30 @@ export const DEFAULT_SETTINGS: AppSettings = {
   showPreviewOnSelect: false,
   sidebarWidth: 220,
   terminalHeight: 200,
+  lowSpaceAlerts: true,
+  lowSpacePercent: 10,
+  lowSpaceMinFreeMb: 1024,
+  compressionMethod: "deflate",
+  compressionLevel: null,
+  archivePermissions: true,
+  archiveHidden: true,
 };
 
+// set_space_alerts; a volume is low once both limits are crossed
+export interface SpaceAlertOptions {
+  enabled: boolean;
+  min_free_percent: number;
+  min_free_bytes: number;
+}
+
+// Payload of the "low-space" event
+export interface LowSpaceEvent {
+  path: string;
+  available_bytes: number;
+  total_bytes: number;
+  percent_free: number;
+}
+
 // Terminal
 export interface TerminalTab {
   id: string;
@@ -154,13 +371,18 @@ export interface RecycleBinItem {
   is_dir: boolean;
 }
 
-// USB Drive
+// USB Drive; `path` is empty while the volume is not mounted
 export interface UsbDrive {
   name: string;
   path: string;
   total_space: number;
   free_space: number;
   drive_type: string;
+  device: string | null;
+  bus: string | null;
+  mounted: boolean;
+  fs_type: string | null;
+  uuid: string | null;
 }
 
 // File Explorer Tab
@@ -175,8 +397,11 @@ export interface DriveSpaceInfo {
   path: string;
   total_bytes: number;
   free_bytes: number;
+  available_bytes: number;
   used_bytes: number;
   percent_used: number;
+  total_inodes: number;
+  free_inodes: number;
 }
 
 // Grouping
@@ -192,3 +417,99 @@ export type FileFilterType =
   | "audio"
   | "archives"
   | "code";
+
+// compute_hashes; digests are lowercase hex
+export type HashAlgorithm = "md5" | "sha1" | "sha256" | "sha512" | "blake3" | "crc32";
+
+export interface FileHashes {
+  path: string;
+  size: number;
+  hashes: Partial<Record<HashAlgorithm, string>>;
+  error: string | null;
+}
+
+// Payload of the "hash-progress" event
+export interface HashProgress {
+  id: number;
+  path: string;
+  file_index: number;
+  file_count: number;
+  bytes_done: number;
+  bytes_total: number;
+  result: FileHashes | null;
+  done: boolean;
+  cancelled: boolean;
+}
+
+// verify_manifest / create_manifest
+export type ManifestFormat = "sum" | "bsd" | "sfv";
+
+export type CheckStatus = "ok" | "mismatch" | "missing" | "unreadable";
+
+export interface ManifestCheck {
+  name: string;
+  path: string;
+  status: CheckStatus;
+  expected: string;
+  actual: string | null;
+  error: string | null;
+}
+
+export interface ManifestReport {
+  manifest: string;
+  algorithm: HashAlgorithm;
+  checks: ManifestCheck[];
+  ok: number;
+  mismatched: number;
+  missing: number;
+  unreadable: number;
+  malformed_lines: number;
+}
+
+// Payload of the "manifest-progress" event
+export interface ManifestProgress {
+  manifest: string;
+  files_done: number;
+  file_count: number;
+  bytes_done: number;
+  bytes_total: number;
+}
+
+export interface ManifestSummary {
+  manifest: string;
+  algorithm: HashAlgorithm;
+  files: number;
+  failed: string[];
+}
+
+// compress_items; extraction detects the format itself
+export type ArchiveFormat =
+  | "zip"
+  | "tar"
+  | "tar_gz"
+  | "tar_xz"
+  | "tar_zst"
+  | "tar_bz2";
+
+// Method only applies to zips; a null level uses the format's default
+export type CompressionMethod = "store" | "deflate" | "bzip2" | "zstd";
+
+export interface CompressOptions {
+  method: CompressionMethod;
+  level: number | null;
+  preserve_permissions: boolean;
+  include_hidden: boolean;
+}
+
+export type ArchiveErrorKind =
+  | "unsupported_format"
+  | "unsupported_method"
+  | "encrypted"
+  | "corrupt"
+  | "io";
+
+// Error of extract_archive
+export interface ArchiveError {
+  kind: ArchiveErrorKind;
+  message: string;
+}
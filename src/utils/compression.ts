@@ -0,0 +1,47 @@
+import type { ArchiveFormat, CompressionMethod } from "../types";
+
+type LevelRange = [number, number] | null;
+
+// Levels each zip method accepts; storing has none
+const METHOD_LEVELS: Record<CompressionMethod, LevelRange> = {
+  store: null,
+  deflate: [0, 9],
+  bzip2: [1, 9],
+  zstd: [1, 22],
+};
+
+// Levels of a tarball's compression; plain tar has none
+const TAR_LEVELS: Record<Exclude<ArchiveFormat, "zip">, LevelRange> = {
+  tar: null,
+  tar_gz: [0, 9],
+  tar_xz: [0, 9],
+  tar_zst: [1, 22],
+  tar_bz2: [1, 9],
+};
+
+export function compressionLevelRange(method: CompressionMethod): LevelRange {
+  return METHOD_LEVELS[method];
+}
+
+// Keep a level within what the method accepts; null stays the default
+export function clampCompressionLevel(
+  method: CompressionMethod,
+  level: number | null,
+): number | null {
+  const range = METHOD_LEVELS[method];
+  if (level === null || !range) return null;
+  return Math.min(Math.max(level, range[0]), range[1]);
+}
+
+// The level to send to compress_items. The setting is chosen for the zip
+// method, so tarballs fall back to their default when it does not fit them.
+export function compressionLevelFor(
+  format: ArchiveFormat,
+  method: CompressionMethod,
+  level: number | null,
+): number | null {
+  if (format === "zip") return clampCompressionLevel(method, level);
+  const range = TAR_LEVELS[format];
+  if (level === null || !range) return null;
+  return level >= range[0] && level <= range[1] ? level : null;
+}
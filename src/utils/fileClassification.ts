@@ -147,3 +147,52 @@ export function classifyFile(ext: string): PreviewKind {
   if (BINARY_EXTS.has(e)) return "binary";
   return "unsupported";
 }
+
+const PREVIEW_KINDS = new Set<PreviewKind>([
+  "text",
+  "code",
+  "markdown",
+  "image",
+  "svg",
+  "audio",
+  "video",
+  "pdf",
+  "csv",
+  "json",
+  "binary",
+]);
+
+/** Kind reported by the backend's content sniffing, if it is a preview kind */
+export function previewKindFromDetected(kind: string): PreviewKind | null {
+  return PREVIEW_KINDS.has(kind as PreviewKind) ? (kind as PreviewKind) : null;
+}
+
+/* ─── Archives ─── */
+
+export const ARCHIVE_EXTENSIONS = [
+  ".zip",
+  ".7z",
+  ".rar",
+  ".tar",
+  ".gz",
+  ".tgz",
+  ".xz",
+  ".txz",
+  ".zst",
+  ".tzst",
+  ".bz2",
+  ".tbz2",
+];
+
+export function isArchiveName(name: string): boolean {
+  const lower = name.toLowerCase();
+  return ARCHIVE_EXTENSIONS.some((ext) => lower.endsWith(ext));
+}
+
+/**
+ * Whether a path may run through an archive, e.g. /home/me/backup.zip/docs.
+ * Judged by name only; list_archive reports whether it really did.
+ */
+export function isArchivePath(path: string): boolean {
+  return path.split(/[\\/]/).some(isArchiveName);
+}